@@ -0,0 +1,65 @@
+#[macro_use]
+extern crate criterion;
+
+use criterion::{black_box, BenchmarkId, Criterion};
+
+use bst_rs::*;
+
+// every element of the table plus as many misses, so that no single branch is always taken
+fn probes<T: SIMDField, const N: usize>(arr: &[T; N]) -> Vec<T> {
+    arr.iter()
+        .flat_map(|n| [*n, *n + T::one()])
+        .collect::<Vec<_>>()
+}
+
+macro_rules! bench_table {
+    ($c:expr, $t:ty, $n:expr) => {{
+        let arr: [$t; $n] = std::array::from_fn(|i| (i * 2) as $t);
+        let keys = probes(&arr);
+        let mut group = $c.benchmark_group(format!("{}x{}", stringify!($t), $n));
+        group
+            .warm_up_time(std::time::Duration::from_millis(500))
+            .measurement_time(std::time::Duration::from_secs(3));
+        group.bench_with_input(BenchmarkId::new("search_array", $n), &arr, |b, arr| {
+            b.iter(|| {
+                keys.iter()
+                    .filter(|k| search_array(black_box(arr), **k).is_some())
+                    .count()
+            })
+        });
+        group.bench_with_input(
+            BenchmarkId::new("binary_search_auto", $n),
+            &arr,
+            |b, arr| {
+                b.iter(|| {
+                    keys.iter()
+                        .filter(|k| binary_search_auto(black_box(arr), **k).is_some())
+                        .count()
+                })
+            },
+        );
+        group.bench_with_input(BenchmarkId::new("std", $n), &arr, |b, arr| {
+            b.iter(|| {
+                keys.iter()
+                    .filter(|k| black_box(arr).binary_search(k).is_ok())
+                    .count()
+            })
+        });
+        group.finish();
+    }};
+}
+
+fn bench(c: &mut Criterion) {
+    bench_table!(c, u16, 8);
+    bench_table!(c, u32, 16);
+    bench_table!(c, u64, 32);
+    bench_table!(c, u8, 64);
+    bench_table!(c, u16, 64);
+    bench_table!(c, u8, 128);
+    bench_table!(c, u16, 128);
+    bench_table!(c, u32, 64);
+    bench_table!(c, u32, 128);
+}
+
+criterion_group!(benches, bench);
+criterion_main!(benches);
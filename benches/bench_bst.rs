@@ -1,4 +1,8 @@
-#![allow(non_upper_case_globals)]
+#![allow(
+    non_upper_case_globals,
+    clippy::useless_conversion,
+    clippy::needless_borrow
+)]
 
 #[macro_use]
 extern crate criterion;
@@ -64,6 +68,14 @@ fn do_simd_bench<T: SIMDField>(b: &mut Bencher, nums: &[T]) {
     });
 }
 
+fn do_scan_bench<T: SIMDField>(b: &mut Bencher, nums: &[T]) {
+    let last = nums.last().unwrap();
+    let last = *last;
+    b.iter(|| {
+        black_box(linear_search(&nums, last).is_some());
+    });
+}
+
 fn do_std_bench<T: num::Integer + num::FromPrimitive>(b: &mut Bencher, nums: &[T]) {
     let last = nums.last().unwrap();
     b.iter(|| black_box(nums.binary_search(last).is_ok()));
@@ -185,6 +197,118 @@ fn optimize_bst_bench(c: &mut Criterion, label: &str) {
     group.finish();
 }
 
+fn scan_bench(c: &mut Criterion, label: &str) {
+    let mut group = c.benchmark_group(label);
+    group
+        .warm_up_time(std::time::Duration::from_millis(500))
+        .measurement_time(std::time::Duration::from_secs(10));
+    group.bench_with_input(BenchmarkId::new("scan_on_8bit", 4), &**U8x4, do_scan_bench);
+    group.bench_with_input(
+        BenchmarkId::new("scan_on_8bit", 16),
+        &**U8x16,
+        do_scan_bench,
+    );
+    group.bench_with_input(
+        BenchmarkId::new("scan_on_8bit", 128),
+        &**U8x128,
+        do_scan_bench,
+    );
+    //
+    group.bench_with_input(
+        BenchmarkId::new("scan_on_16bit", 4),
+        &**U16x4,
+        do_scan_bench,
+    );
+    group.bench_with_input(
+        BenchmarkId::new("scan_on_16bit", 16),
+        &**U16x16,
+        do_scan_bench,
+    );
+    group.bench_with_input(
+        BenchmarkId::new("scan_on_16bit", 128),
+        &**U16x128,
+        do_scan_bench,
+    );
+    group.bench_with_input(
+        BenchmarkId::new("scan_on_16bit", 512),
+        &**U16x512,
+        do_scan_bench,
+    );
+    group.bench_with_input(
+        BenchmarkId::new("scan_on_16bit", 2048),
+        &**U16x2048,
+        do_scan_bench,
+    );
+    group.bench_with_input(
+        BenchmarkId::new("scan_on_16bit", 8192),
+        &**U16x8192,
+        do_scan_bench,
+    );
+    //
+    group.bench_with_input(
+        BenchmarkId::new("scan_on_32bit", 4),
+        &**U32x4,
+        do_scan_bench,
+    );
+    group.bench_with_input(
+        BenchmarkId::new("scan_on_32bit", 16),
+        &**U32x16,
+        do_scan_bench,
+    );
+    group.bench_with_input(
+        BenchmarkId::new("scan_on_32bit", 128),
+        &**U32x128,
+        do_scan_bench,
+    );
+    group.bench_with_input(
+        BenchmarkId::new("scan_on_32bit", 512),
+        &**U32x512,
+        do_scan_bench,
+    );
+    group.bench_with_input(
+        BenchmarkId::new("scan_on_32bit", 2048),
+        &**U32x2048,
+        do_scan_bench,
+    );
+    group.bench_with_input(
+        BenchmarkId::new("scan_on_32bit", 8192),
+        &**U32x8192,
+        do_scan_bench,
+    );
+    //
+    group.bench_with_input(
+        BenchmarkId::new("scan_on_64bit", 4),
+        &**U64x4,
+        do_scan_bench,
+    );
+    group.bench_with_input(
+        BenchmarkId::new("scan_on_64bit", 16),
+        &**U64x16,
+        do_scan_bench,
+    );
+    group.bench_with_input(
+        BenchmarkId::new("scan_on_64bit", 128),
+        &**U64x128,
+        do_scan_bench,
+    );
+    group.bench_with_input(
+        BenchmarkId::new("scan_on_64bit", 512),
+        &**U64x512,
+        do_scan_bench,
+    );
+    group.bench_with_input(
+        BenchmarkId::new("scan_on_64bit", 2048),
+        &**U64x2048,
+        do_scan_bench,
+    );
+    group.bench_with_input(
+        BenchmarkId::new("scan_on_64bit", 8192),
+        &**U64x8192,
+        do_scan_bench,
+    );
+    group.finish();
+}
+
 fn std_bst_bench(c: &mut Criterion, label: &str) {
     let mut group = c.benchmark_group(label);
     group
@@ -284,6 +408,7 @@ fn std_bst_bench(c: &mut Criterion, label: &str) {
 
 fn bench(c: &mut Criterion) {
     optimize_bst_bench(c, "SIMDS");
+    scan_bench(c, "scan");
     std_bst_bench(c, "std");
 }
 
@@ -0,0 +1,23 @@
+fn main() {
+    // the C test in `tests/c_program.rs` builds for the same target
+    println!(
+        "cargo:rustc-env=BST_TARGET={}",
+        std::env::var("TARGET").unwrap()
+    );
+    generate_header();
+}
+
+// generate `bst.h` into `OUT_DIR` from the C ABI in `src/lib.rs`
+fn generate_header() {
+    println!("cargo:rerun-if-changed=src/lib.rs");
+    println!("cargo:rerun-if-changed=cbindgen.toml");
+    let crate_dir = std::env::var("CARGO_MANIFEST_DIR").unwrap();
+    let out_dir = std::env::var("OUT_DIR").unwrap();
+    let config = cbindgen::Config::from_file(format!("{}/cbindgen.toml", crate_dir)).unwrap();
+    cbindgen::Builder::new()
+        .with_config(config)
+        .with_src(format!("{}/src/lib.rs", crate_dir))
+        .generate()
+        .expect("unable to generate the C header")
+        .write_to_file(format!("{}/bst.h", out_dir));
+}
@@ -0,0 +1,143 @@
+/*
+ * every element type T gets the same pair of functions:
+ *
+ * int bst_search_T(nums, len, target, out)
+ *     finds target in the sorted nums[0..len], writes the index of its first occurrence
+ *     to out and returns 1, or returns 0 if missing
+ *
+ * ptrdiff_t bst_search_batch_T(nums, len, targets, count, out)
+ *     the same for count targets, out[j] is the index of targets[j] or BST_NOT_FOUND,
+ *     returns the number found
+ *
+ * both return -1 on a null pointer, null is fine wherever the length is 0
+ *
+ * safety: nums must point to len readable elements, targets to count readable elements
+ * and out to as many writable size_t as there are targets
+ */
+
+#ifndef BST_H
+#define BST_H
+
+/* generated by cbindgen from ffi/src/lib.rs, do not edit */
+
+#include <stddef.h>
+#include <stdint.h>
+
+/**
+ * written to `out` by the batch functions for targets that are missing
+ */
+#define BST_NOT_FOUND (size_t)~0
+
+/**
+ * `bst_search` over `uint8_t`
+ */
+int bst_search_u8(const uint8_t *nums, size_t len, uint8_t target, size_t *out);
+
+/**
+ * `bst_search_batch` over `uint8_t`
+ */
+ptrdiff_t bst_search_batch_u8(const uint8_t *nums,
+                              size_t len,
+                              const uint8_t *targets,
+                              size_t count,
+                              size_t *out);
+
+/**
+ * `bst_search` over `int8_t`
+ */
+int bst_search_i8(const int8_t *nums, size_t len, int8_t target, size_t *out);
+
+/**
+ * `bst_search_batch` over `int8_t`
+ */
+ptrdiff_t bst_search_batch_i8(const int8_t *nums,
+                              size_t len,
+                              const int8_t *targets,
+                              size_t count,
+                              size_t *out);
+
+/**
+ * `bst_search` over `uint16_t`
+ */
+int bst_search_u16(const uint16_t *nums, size_t len, uint16_t target, size_t *out);
+
+/**
+ * `bst_search_batch` over `uint16_t`
+ */
+ptrdiff_t bst_search_batch_u16(const uint16_t *nums,
+                               size_t len,
+                               const uint16_t *targets,
+                               size_t count,
+                               size_t *out);
+
+/**
+ * `bst_search` over `int16_t`
+ */
+int bst_search_i16(const int16_t *nums, size_t len, int16_t target, size_t *out);
+
+/**
+ * `bst_search_batch` over `int16_t`
+ */
+ptrdiff_t bst_search_batch_i16(const int16_t *nums,
+                               size_t len,
+                               const int16_t *targets,
+                               size_t count,
+                               size_t *out);
+
+/**
+ * `bst_search` over `uint32_t`
+ */
+int bst_search_u32(const uint32_t *nums, size_t len, uint32_t target, size_t *out);
+
+/**
+ * `bst_search_batch` over `uint32_t`
+ */
+ptrdiff_t bst_search_batch_u32(const uint32_t *nums,
+                               size_t len,
+                               const uint32_t *targets,
+                               size_t count,
+                               size_t *out);
+
+/**
+ * `bst_search` over `int32_t`
+ */
+int bst_search_i32(const int32_t *nums, size_t len, int32_t target, size_t *out);
+
+/**
+ * `bst_search_batch` over `int32_t`
+ */
+ptrdiff_t bst_search_batch_i32(const int32_t *nums,
+                               size_t len,
+                               const int32_t *targets,
+                               size_t count,
+                               size_t *out);
+
+/**
+ * `bst_search` over `uint64_t`
+ */
+int bst_search_u64(const uint64_t *nums, size_t len, uint64_t target, size_t *out);
+
+/**
+ * `bst_search_batch` over `uint64_t`
+ */
+ptrdiff_t bst_search_batch_u64(const uint64_t *nums,
+                               size_t len,
+                               const uint64_t *targets,
+                               size_t count,
+                               size_t *out);
+
+/**
+ * `bst_search` over `int64_t`
+ */
+int bst_search_i64(const int64_t *nums, size_t len, int64_t target, size_t *out);
+
+/**
+ * `bst_search_batch` over `int64_t`
+ */
+ptrdiff_t bst_search_batch_i64(const int64_t *nums,
+                               size_t len,
+                               const int64_t *targets,
+                               size_t count,
+                               size_t *out);
+
+#endif  /* BST_H */
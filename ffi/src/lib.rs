@@ -0,0 +1,260 @@
+//! C ABI over the search kernels
+//!
+//! the contract shared by every function is written once, in the `header` of `cbindgen.toml`,
+//! and lands at the top of `include/bst.h`; `build.rs` generates the header into `OUT_DIR`
+//!
+//! the functions are spelled out per type instead of generated by a macro, cbindgen can't
+//! see through macros on stable
+
+// the safety contract is in the header, shared by every function
+#![allow(clippy::missing_safety_doc)]
+
+use std::os::raw::c_int;
+
+use bst_rs::{searchsorted, SIMDField, Side};
+
+/// written to `out` by the batch functions for targets that are missing
+// the cast makes cbindgen emit `(size_t)~0` instead of an `int`
+#[allow(clippy::unnecessary_cast)]
+pub const BST_NOT_FOUND: usize = !0 as usize;
+
+unsafe fn search<T: SIMDField>(nums: *const T, len: usize, target: T, out: *mut usize) -> c_int {
+    let nums = match slice(nums, len) {
+        Some(nums) => nums,
+        None => return -1,
+    };
+    if out.is_null() {
+        return -1;
+    }
+    let mut rank = [0];
+    searchsorted(nums, &[target], Side::Left, &mut rank);
+    match nums.get(rank[0]) {
+        Some(n) if *n == target => {
+            *out = rank[0];
+            1
+        }
+        _ => 0,
+    }
+}
+
+unsafe fn search_many<T: SIMDField>(
+    nums: *const T,
+    len: usize,
+    targets: *const T,
+    count: usize,
+    out: *mut usize,
+) -> isize {
+    let (nums, targets) = match (slice(nums, len), slice(targets, count)) {
+        (Some(nums), Some(targets)) => (nums, targets),
+        _ => return -1,
+    };
+    let out = match slice(out as *const usize, count) {
+        Some(_) if count > 0 => std::slice::from_raw_parts_mut(out, count),
+        Some(_) => &mut [],
+        None => return -1,
+    };
+    // lower bound of every target, kept only on an exact hit
+    searchsorted(nums, targets, Side::Left, out);
+    let mut found = 0;
+    for (i, target) in out.iter_mut().zip(targets) {
+        match nums.get(*i) {
+            Some(n) if n == target => found += 1,
+            _ => *i = BST_NOT_FOUND,
+        }
+    }
+    found
+}
+
+// null is only fine for empty slices
+#[inline(always)]
+unsafe fn slice<'a, T>(ptr: *const T, len: usize) -> Option<&'a [T]> {
+    match (ptr.is_null(), len) {
+        (_, 0) => Some(&[]),
+        (true, _) => None,
+        (false, len) => Some(std::slice::from_raw_parts(ptr, len)),
+    }
+}
+
+/// `bst_search` over `uint8_t`
+#[no_mangle]
+pub unsafe extern "C" fn bst_search_u8(
+    nums: *const u8,
+    len: usize,
+    target: u8,
+    out: *mut usize,
+) -> c_int {
+    search(nums, len, target, out)
+}
+
+/// `bst_search_batch` over `uint8_t`
+#[no_mangle]
+pub unsafe extern "C" fn bst_search_batch_u8(
+    nums: *const u8,
+    len: usize,
+    targets: *const u8,
+    count: usize,
+    out: *mut usize,
+) -> isize {
+    search_many(nums, len, targets, count, out)
+}
+
+/// `bst_search` over `int8_t`
+#[no_mangle]
+pub unsafe extern "C" fn bst_search_i8(
+    nums: *const i8,
+    len: usize,
+    target: i8,
+    out: *mut usize,
+) -> c_int {
+    search(nums, len, target, out)
+}
+
+/// `bst_search_batch` over `int8_t`
+#[no_mangle]
+pub unsafe extern "C" fn bst_search_batch_i8(
+    nums: *const i8,
+    len: usize,
+    targets: *const i8,
+    count: usize,
+    out: *mut usize,
+) -> isize {
+    search_many(nums, len, targets, count, out)
+}
+
+/// `bst_search` over `uint16_t`
+#[no_mangle]
+pub unsafe extern "C" fn bst_search_u16(
+    nums: *const u16,
+    len: usize,
+    target: u16,
+    out: *mut usize,
+) -> c_int {
+    search(nums, len, target, out)
+}
+
+/// `bst_search_batch` over `uint16_t`
+#[no_mangle]
+pub unsafe extern "C" fn bst_search_batch_u16(
+    nums: *const u16,
+    len: usize,
+    targets: *const u16,
+    count: usize,
+    out: *mut usize,
+) -> isize {
+    search_many(nums, len, targets, count, out)
+}
+
+/// `bst_search` over `int16_t`
+#[no_mangle]
+pub unsafe extern "C" fn bst_search_i16(
+    nums: *const i16,
+    len: usize,
+    target: i16,
+    out: *mut usize,
+) -> c_int {
+    search(nums, len, target, out)
+}
+
+/// `bst_search_batch` over `int16_t`
+#[no_mangle]
+pub unsafe extern "C" fn bst_search_batch_i16(
+    nums: *const i16,
+    len: usize,
+    targets: *const i16,
+    count: usize,
+    out: *mut usize,
+) -> isize {
+    search_many(nums, len, targets, count, out)
+}
+
+/// `bst_search` over `uint32_t`
+#[no_mangle]
+pub unsafe extern "C" fn bst_search_u32(
+    nums: *const u32,
+    len: usize,
+    target: u32,
+    out: *mut usize,
+) -> c_int {
+    search(nums, len, target, out)
+}
+
+/// `bst_search_batch` over `uint32_t`
+#[no_mangle]
+pub unsafe extern "C" fn bst_search_batch_u32(
+    nums: *const u32,
+    len: usize,
+    targets: *const u32,
+    count: usize,
+    out: *mut usize,
+) -> isize {
+    search_many(nums, len, targets, count, out)
+}
+
+/// `bst_search` over `int32_t`
+#[no_mangle]
+pub unsafe extern "C" fn bst_search_i32(
+    nums: *const i32,
+    len: usize,
+    target: i32,
+    out: *mut usize,
+) -> c_int {
+    search(nums, len, target, out)
+}
+
+/// `bst_search_batch` over `int32_t`
+#[no_mangle]
+pub unsafe extern "C" fn bst_search_batch_i32(
+    nums: *const i32,
+    len: usize,
+    targets: *const i32,
+    count: usize,
+    out: *mut usize,
+) -> isize {
+    search_many(nums, len, targets, count, out)
+}
+
+/// `bst_search` over `uint64_t`
+#[no_mangle]
+pub unsafe extern "C" fn bst_search_u64(
+    nums: *const u64,
+    len: usize,
+    target: u64,
+    out: *mut usize,
+) -> c_int {
+    search(nums, len, target, out)
+}
+
+/// `bst_search_batch` over `uint64_t`
+#[no_mangle]
+pub unsafe extern "C" fn bst_search_batch_u64(
+    nums: *const u64,
+    len: usize,
+    targets: *const u64,
+    count: usize,
+    out: *mut usize,
+) -> isize {
+    search_many(nums, len, targets, count, out)
+}
+
+/// `bst_search` over `int64_t`
+#[no_mangle]
+pub unsafe extern "C" fn bst_search_i64(
+    nums: *const i64,
+    len: usize,
+    target: i64,
+    out: *mut usize,
+) -> c_int {
+    search(nums, len, target, out)
+}
+
+/// `bst_search_batch` over `int64_t`
+#[no_mangle]
+pub unsafe extern "C" fn bst_search_batch_i64(
+    nums: *const i64,
+    len: usize,
+    targets: *const i64,
+    count: usize,
+    out: *mut usize,
+) -> isize {
+    search_many(nums, len, targets, count, out)
+}
@@ -0,0 +1,87 @@
+/* exercises the C ABI through the generated header, exits non-zero on the first failure */
+#include <stdio.h>
+
+#include "bst.h"
+
+#define CHECK(cond)                                                   \
+    do {                                                              \
+        if (!(cond)) {                                                \
+            fprintf(stderr, "%s:%d: %s failed\n", __FILE__, __LINE__, #cond); \
+            return 1;                                                 \
+        }                                                             \
+    } while (0)
+
+#define LEN 1000
+
+static int test_u32(void) {
+    uint32_t nums[LEN];
+    for (size_t i = 0; i < LEN; i++) {
+        nums[i] = (uint32_t)(i * 3);
+    }
+    size_t out = 0;
+    CHECK(bst_search_u32(nums, LEN, 300, &out) == 1 && out == 100);
+    CHECK(bst_search_u32(nums, LEN, 301, &out) == 0);
+    CHECK(bst_search_u32(nums, LEN, 4000000000u, &out) == 0);
+    CHECK(bst_search_u32(nums, 0, 0, &out) == 0);
+    CHECK(bst_search_u32(NULL, LEN, 0, &out) == -1);
+    CHECK(bst_search_u32(nums, LEN, 0, NULL) == -1);
+
+    uint32_t targets[LEN];
+    size_t hits[LEN];
+    for (size_t i = 0; i < LEN; i++) {
+        targets[i] = (uint32_t)i;
+    }
+    CHECK(bst_search_batch_u32(nums, LEN, targets, LEN, hits) == 334);
+    for (size_t i = 0; i < LEN; i++) {
+        CHECK(hits[i] == (i % 3 == 0 ? i / 3 : BST_NOT_FOUND));
+    }
+    CHECK(bst_search_batch_u32(nums, LEN, NULL, 0, NULL) == 0);
+    CHECK(bst_search_batch_u32(nums, LEN, targets, LEN, NULL) == -1);
+    return 0;
+}
+
+static int test_signed(void) {
+    int8_t i8s[200];
+    int16_t i16s[200];
+    int64_t i64s[200];
+    for (int i = 0; i < 200; i++) {
+        i8s[i] = (int8_t)(i - 100);
+        i16s[i] = (int16_t)(i * 100 - 10000);
+        i64s[i] = (int64_t)i * 1000000000000LL - 100000000000000LL;
+    }
+    size_t out = 0;
+    CHECK(bst_search_i8(i8s, 200, -100, &out) == 1 && out == 0);
+    CHECK(bst_search_i8(i8s, 200, 99, &out) == 1 && out == 199);
+    CHECK(bst_search_i16(i16s, 200, -9900, &out) == 1 && out == 1);
+    CHECK(bst_search_i16(i16s, 200, -9901, &out) == 0);
+    CHECK(bst_search_i64(i64s, 200, 0, &out) == 1 && out == 100);
+    CHECK(bst_search_i64(i64s, 200, INT64_MIN, &out) == 0);
+    return 0;
+}
+
+static int test_unsigned(void) {
+    uint8_t u8s[] = {1, 1, 2, 3, 5, 8, 13, 21, 34, 55, 89, 144, 233};
+    uint16_t u16s[] = {0, 65535};
+    uint64_t u64s[] = {0, 1, UINT64_MAX};
+    int32_t i32s[] = {-7, 0, 7};
+    size_t out = 0;
+    CHECK(bst_search_u8(u8s, 13, 1, &out) == 1 && out == 0);
+    CHECK(bst_search_u8(u8s, 13, 233, &out) == 1 && out == 12);
+    CHECK(bst_search_u16(u16s, 2, 65535, &out) == 1 && out == 1);
+    CHECK(bst_search_u64(u64s, 3, UINT64_MAX, &out) == 1 && out == 2);
+    CHECK(bst_search_i32(i32s, 3, -7, &out) == 1 && out == 0);
+
+    uint64_t targets[] = {UINT64_MAX, 2, 0};
+    size_t hits[3];
+    CHECK(bst_search_batch_u64(u64s, 3, targets, 3, hits) == 2);
+    CHECK(hits[0] == 2 && hits[1] == BST_NOT_FOUND && hits[2] == 0);
+    return 0;
+}
+
+int main(void) {
+    if (test_u32() || test_signed() || test_unsigned()) {
+        return 1;
+    }
+    printf("ffi_test: ok\n");
+    return 0;
+}
@@ -0,0 +1,58 @@
+//! builds `tests/c/ffi_test.c` against the generated header and the cdylib, then runs it
+
+use std::path::Path;
+use std::process::Command;
+
+#[test]
+fn test_c_program() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    // the cdylib is built next to this test binary
+    let exe = std::env::current_exe().unwrap();
+    let deps = exe.parent().unwrap();
+    let lib = deps.join(format!(
+        "{}bst_ffi{}",
+        std::env::consts::DLL_PREFIX,
+        std::env::consts::DLL_SUFFIX
+    ));
+    assert!(lib.exists(), "missing {}", lib.display());
+
+    let out_dir = tempfile::tempdir().unwrap();
+    let program = out_dir.path().join("ffi_test");
+    let compiler = cc::Build::new()
+        .cargo_metadata(false)
+        .opt_level(0)
+        .target(env!("BST_TARGET"))
+        .host(env!("BST_TARGET"))
+        .get_compiler();
+    let status = compiler
+        .to_command()
+        .arg(root.join("tests/c/ffi_test.c"))
+        .arg("-I")
+        .arg(env!("OUT_DIR"))
+        .arg("-o")
+        .arg(&program)
+        .arg(&lib)
+        .arg(format!("-Wl,-rpath,{}", deps.display()))
+        .status()
+        .unwrap();
+    assert!(status.success(), "compiling ffi_test.c failed");
+
+    let output = Command::new(&program).output().unwrap();
+    assert!(
+        output.status.success(),
+        "ffi_test failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+#[test]
+fn test_header_up_to_date() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let generated = std::fs::read_to_string(Path::new(env!("OUT_DIR")).join("bst.h")).unwrap();
+    let checked_in = std::fs::read_to_string(root.join("include/bst.h")).unwrap();
+    assert!(
+        generated == checked_in,
+        "include/bst.h is stale, copy it from {}",
+        env!("OUT_DIR")
+    );
+}
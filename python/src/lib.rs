@@ -0,0 +1,135 @@
+//! NumPy bindings, every function dispatches on the dtype to the monomorphized kernel
+
+use bst_rs::{
+    binary_search_auto, linear_search, searchsorted as simd_searchsorted, SIMDField, Side,
+};
+use numpy::{Element, PyArray1, PyReadonlyArray1};
+use pyo3::exceptions::{PyTypeError, PyValueError};
+use pyo3::prelude::*;
+
+// call `$f::<T>(nums, $args)` with `nums` borrowed as `&[T]` for the dtype of `$nums`
+macro_rules! dispatch {
+    ($nums:expr, $f:ident($($arg:expr),*)) => {{
+        let nums = $nums;
+        if let Ok(a) = nums.extract::<PyReadonlyArray1<u8>>() {
+            $f::<u8>(a.as_slice()?, $($arg),*)
+        } else if let Ok(a) = nums.extract::<PyReadonlyArray1<i8>>() {
+            $f::<i8>(a.as_slice()?, $($arg),*)
+        } else if let Ok(a) = nums.extract::<PyReadonlyArray1<u16>>() {
+            $f::<u16>(a.as_slice()?, $($arg),*)
+        } else if let Ok(a) = nums.extract::<PyReadonlyArray1<i16>>() {
+            $f::<i16>(a.as_slice()?, $($arg),*)
+        } else if let Ok(a) = nums.extract::<PyReadonlyArray1<u32>>() {
+            $f::<u32>(a.as_slice()?, $($arg),*)
+        } else if let Ok(a) = nums.extract::<PyReadonlyArray1<i32>>() {
+            $f::<i32>(a.as_slice()?, $($arg),*)
+        } else if let Ok(a) = nums.extract::<PyReadonlyArray1<u64>>() {
+            $f::<u64>(a.as_slice()?, $($arg),*)
+        } else if let Ok(a) = nums.extract::<PyReadonlyArray1<i64>>() {
+            $f::<i64>(a.as_slice()?, $($arg),*)
+        } else {
+            Err(PyTypeError::new_err(
+                "expected a 1-D array of uint8, int8, uint16, int16, uint32, int32, uint64 or int64",
+            ))
+        }
+    }};
+}
+
+/// index of `target` in `nums` or `None`
+#[pyfunction]
+#[pyo3(signature = (nums, target, sorted = true))]
+fn search(
+    nums: &Bound<'_, PyAny>,
+    target: &Bound<'_, PyAny>,
+    sorted: bool,
+) -> PyResult<Option<usize>> {
+    dispatch!(nums, search_typed(target, sorted))
+}
+
+/// whether `target` is in `nums`
+#[pyfunction]
+#[pyo3(signature = (nums, target, sorted = true))]
+fn contains(nums: &Bound<'_, PyAny>, target: &Bound<'_, PyAny>, sorted: bool) -> PyResult<bool> {
+    Ok(search(nums, target, sorted)?.is_some())
+}
+
+/// `numpy.searchsorted` for a 1-D `v` of the same dtype as `a`
+#[pyfunction]
+#[pyo3(signature = (a, v, side = "left"))]
+fn searchsorted<'py>(
+    a: &Bound<'py, PyAny>,
+    v: &Bound<'py, PyAny>,
+    side: &str,
+) -> PyResult<Bound<'py, PyArray1<isize>>> {
+    let side = match side {
+        "left" => Side::Left,
+        "right" => Side::Right,
+        _ => return Err(PyValueError::new_err("side must be 'left' or 'right'")),
+    };
+    dispatch!(a, searchsorted_typed(v, side))
+}
+
+/// first index of every target in the sorted `nums`, `-1` when missing
+#[pyfunction]
+fn search_batch<'py>(
+    nums: &Bound<'py, PyAny>,
+    targets: &Bound<'py, PyAny>,
+) -> PyResult<Bound<'py, PyArray1<i64>>> {
+    dispatch!(nums, search_batch_typed(targets))
+}
+
+fn search_typed<T: SIMDField + Element + for<'a, 'py> FromPyObject<'a, 'py, Error = PyErr>>(
+    nums: &[T],
+    target: &Bound<'_, PyAny>,
+    sorted: bool,
+) -> PyResult<Option<usize>> {
+    let target = target.extract::<T>()?;
+    if !sorted {
+        return Ok(linear_search(nums, target));
+    }
+    Ok(binary_search_auto(nums, target))
+}
+
+fn searchsorted_typed<'py, T: SIMDField + Element>(
+    edges: &[T],
+    values: &Bound<'py, PyAny>,
+    side: Side,
+) -> PyResult<Bound<'py, PyArray1<isize>>> {
+    let py = values.py();
+    let values = values.extract::<PyReadonlyArray1<T>>()?;
+    let values = values.as_slice()?;
+    let mut out = vec![0usize; values.len()];
+    simd_searchsorted(edges, values, side, &mut out);
+    // numpy reports insertion points as `intp`
+    let out = out.into_iter().map(|i| i as isize).collect();
+    Ok(PyArray1::from_vec(py, out))
+}
+
+fn search_batch_typed<'py, T: SIMDField + Element>(
+    nums: &[T],
+    targets: &Bound<'py, PyAny>,
+) -> PyResult<Bound<'py, PyArray1<i64>>> {
+    let py = targets.py();
+    let targets = targets.extract::<PyReadonlyArray1<T>>()?;
+    let targets = targets.as_slice()?;
+    let mut ranks = vec![0usize; targets.len()];
+    simd_searchsorted(nums, targets, Side::Left, &mut ranks);
+    let out = ranks
+        .iter()
+        .zip(targets)
+        .map(|(rank, target)| match nums.get(*rank) {
+            Some(n) if n == target => *rank as i64,
+            _ => -1,
+        })
+        .collect::<Vec<_>>();
+    Ok(PyArray1::from_vec(py, out))
+}
+
+#[pymodule(name = "bst_rs")]
+fn bst_rs_py(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(search, m)?)?;
+    m.add_function(wrap_pyfunction!(contains, m)?)?;
+    m.add_function(wrap_pyfunction!(searchsorted, m)?)?;
+    m.add_function(wrap_pyfunction!(search_batch, m)?)?;
+    Ok(())
+}
@@ -0,0 +1,81 @@
+import numpy as np
+import pytest
+
+import bst_rs
+
+DTYPES = [np.uint8, np.int8, np.uint16, np.int16, np.uint32, np.int32, np.uint64, np.int64]
+
+
+def sorted_array(dtype, size, seed):
+    info = np.iinfo(dtype)
+    rng = np.random.default_rng(seed)
+    nums = rng.integers(info.min, info.max, size=size, dtype=dtype, endpoint=True)
+    nums.sort()
+    return nums
+
+
+def probes(dtype, nums, seed):
+    info = np.iinfo(dtype)
+    rng = np.random.default_rng(seed)
+    extra = rng.integers(info.min, info.max, size=200, dtype=dtype, endpoint=True)
+    ends = np.array([info.min, info.max], dtype=dtype)
+    return np.concatenate([nums[::7], extra, ends]).astype(dtype)
+
+
+@pytest.mark.parametrize("dtype", DTYPES)
+@pytest.mark.parametrize("size", [0, 1, 31, 32, 33, 1000, 100_000])
+def test_searchsorted(dtype, size):
+    nums = sorted_array(dtype, size, size)
+    values = probes(dtype, nums, size + 1)
+    for side in ["left", "right"]:
+        got = bst_rs.searchsorted(nums, values, side=side)
+        assert got.dtype == np.intp
+        np.testing.assert_array_equal(got, np.searchsorted(nums, values, side=side))
+
+
+@pytest.mark.parametrize("dtype", DTYPES)
+@pytest.mark.parametrize("size", [0, 1, 1000, 100_000])
+def test_search_and_contains(dtype, size):
+    nums = sorted_array(dtype, size, size)
+    for value in probes(dtype, nums, size + 2)[:300]:
+        expected = bool(np.any(nums == value))
+        i = bst_rs.search(nums, int(value))
+        assert (i is not None) == expected
+        if i is not None:
+            assert nums[i] == value
+        assert bst_rs.contains(nums, int(value)) == expected
+        j = bst_rs.search(nums[::-1].copy(), int(value), sorted=False)
+        assert (j is not None) == expected
+
+
+@pytest.mark.parametrize("dtype", DTYPES)
+def test_search_out_of_range(dtype):
+    info = np.iinfo(dtype)
+    nums = np.arange(10, 110, dtype=dtype)
+    for value in [int(info.min), 9, 110, int(info.max)]:
+        assert bst_rs.search(nums, value) is None
+        assert not bst_rs.contains(nums, value)
+
+
+@pytest.mark.parametrize("dtype", DTYPES)
+def test_search_batch(dtype):
+    nums = sorted_array(dtype, 5000, 3)
+    targets = probes(dtype, nums, 4)
+    got = bst_rs.search_batch(nums, targets)
+    left = np.searchsorted(nums, targets, side="left")
+    hit = (left < len(nums)) & (nums[np.minimum(left, len(nums) - 1)] == targets)
+    np.testing.assert_array_equal(got, np.where(hit, left, -1))
+
+
+def test_errors():
+    with pytest.raises(TypeError):
+        bst_rs.search(np.arange(10, dtype=np.float64), 3)
+    with pytest.raises(TypeError):
+        bst_rs.searchsorted(np.arange(10, dtype=np.int32), np.arange(3, dtype=np.int64))
+    with pytest.raises(ValueError):
+        bst_rs.searchsorted(np.arange(10, dtype=np.int32), np.arange(3, dtype=np.int32), side="middle")
+    # views with a stride can't be borrowed
+    with pytest.raises(Exception):
+        bst_rs.search(np.arange(10, dtype=np.int32)[::2], 4)
+    with pytest.raises(OverflowError):
+        bst_rs.search(np.arange(10, dtype=np.uint8), 300)
@@ -0,0 +1,302 @@
+use std::fmt;
+use std::hint::select_unpredictable;
+use std::marker::PhantomData;
+use std::mem::size_of;
+
+use crate::simd::linear::{
+    cmpeq, cmpgt, lane_mask, lane_range, lanes, load_aligned, splat, to_signed,
+};
+use crate::simd::{Order, SIMDField, Side};
+
+/// allocation unit of `AlignedSortedVec`, one cache line and a whole number of vectors
+#[derive(Clone, Copy)]
+#[repr(C, align(64))]
+struct Line([u8; 64]);
+
+/// sorted integers in 64-byte aligned storage, padded up to a whole cache line with `T::MAX`,
+/// or `T::MIN` when sorted descending
+///
+/// every vector the kernels load is aligned and lies within the allocation, so searches use
+/// aligned loads and never need a scalar tail
+#[derive(Clone)]
+pub struct AlignedSortedVec<T> {
+    len: usize,
+    order: Order,
+    lines: Vec<Line>,
+    _marker: PhantomData<T>,
+}
+
+impl<T: SIMDField> AlignedSortedVec<T> {
+    /// sorts `nums` in `order`
+    pub fn from_ordered(mut nums: Vec<T>, order: Order) -> Self {
+        match order {
+            Order::Ascending => nums.sort_unstable(),
+            Order::Descending => nums.sort_unstable_by(|a, b| b.cmp(a)),
+        }
+        let per_line = size_of::<Line>() / size_of::<T>();
+        let mut aligned = AlignedSortedVec {
+            len: nums.len(),
+            order,
+            lines: vec![Line([0; 64]); nums.len().div_ceil(per_line)],
+            _marker: PhantomData,
+        };
+        let padded = aligned.padded_mut();
+        padded[..nums.len()].copy_from_slice(&nums);
+        padded[nums.len()..].fill(sentinel(order));
+        aligned
+    }
+
+    pub fn order(&self) -> Order {
+        self.order
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn as_slice(&self) -> &[T] {
+        &self.padded()[..self.len]
+    }
+
+    pub fn get(&self, i: usize) -> Option<T> {
+        self.as_slice().get(i).copied()
+    }
+
+    pub fn to_vec(&self) -> Vec<T> {
+        self.as_slice().to_vec()
+    }
+
+    /// index of the first element equal to `target`
+    pub fn search(&self, target: T) -> Option<usize> {
+        let unit_size = lanes::<T>();
+        let v = self.vector_of(target, Side::Left)?;
+        let from = v * unit_size;
+        let mask = unsafe {
+            let chunk = load_aligned(self.padded()[from..].as_ptr());
+            lane_mask::<T>(cmpeq::<T>(chunk, splat(target)))
+        };
+        // a match in the padding means `target` is the sentinel and missing
+        let i = from + mask.trailing_zeros() as usize;
+        (mask != 0 && i < self.len).then_some(i)
+    }
+
+    pub fn contains(&self, target: T) -> bool {
+        self.search(target).is_some()
+    }
+
+    /// insertion point of `target`, as `searchsorted_ordered` reports it
+    pub fn rank(&self, target: T, side: Side) -> usize {
+        let unit_size = lanes::<T>();
+        let v = match self.vector_of(target, side) {
+            Some(v) => v,
+            None => return self.len,
+        };
+        let from = v * unit_size;
+        let before = unsafe {
+            let chunk = to_signed::<T>(load_aligned(self.padded()[from..].as_ptr()));
+            let keys = to_signed::<T>(splat(target));
+            // `a > b` in the lanes that come before `target`
+            let (a, b) = match self.order {
+                Order::Ascending => (keys, chunk),
+                Order::Descending => (chunk, keys),
+            };
+            match side {
+                Side::Left => lane_mask::<T>(cmpgt::<T>(a, b)),
+                Side::Right => !lane_mask::<T>(cmpgt::<T>(b, a)) & lane_range(unit_size),
+            }
+        };
+        (from + before.count_ones() as usize).min(self.len)
+    }
+
+    // first vector whose last lane doesn't come before `target`, it holds the insertion point
+    fn vector_of(&self, target: T, side: Side) -> Option<usize> {
+        let padded = self.padded();
+        let unit_size = lanes::<T>();
+        let vectors = padded.len() / unit_size;
+        let before = |v: usize| {
+            let last = padded[v * unit_size + unit_size - 1];
+            match side {
+                Side::Left => self.order.before(last, target),
+                Side::Right => !self.order.before(target, last),
+            }
+        };
+        if vectors == 0 {
+            return None;
+        }
+        let (mut base, mut n) = (0, vectors);
+        while n > 1 {
+            let half = n / 2;
+            base = select_unpredictable(before(base + half), base + half, base);
+            n -= half;
+        }
+        let v = base + before(base) as usize;
+        (v < vectors).then_some(v)
+    }
+
+    // the elements followed by the sentinel padding
+    fn padded(&self) -> &[T] {
+        let len = self.lines.len() * size_of::<Line>() / size_of::<T>();
+        // `Line` is plain bytes aligned for any `SIMDField`, which are all plain integers
+        unsafe { std::slice::from_raw_parts(self.lines.as_ptr() as *const T, len) }
+    }
+
+    fn padded_mut(&mut self) -> &mut [T] {
+        let len = self.lines.len() * size_of::<Line>() / size_of::<T>();
+        unsafe { std::slice::from_raw_parts_mut(self.lines.as_mut_ptr() as *mut T, len) }
+    }
+}
+
+/// sorts `nums` ascending
+impl<T: SIMDField> From<Vec<T>> for AlignedSortedVec<T> {
+    fn from(nums: Vec<T>) -> Self {
+        Self::from_ordered(nums, Order::Ascending)
+    }
+}
+
+impl<T: SIMDField> From<AlignedSortedVec<T>> for Vec<T> {
+    fn from(aligned: AlignedSortedVec<T>) -> Self {
+        aligned.to_vec()
+    }
+}
+
+impl<T: SIMDField> FromIterator<T> for AlignedSortedVec<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        iter.into_iter().collect::<Vec<_>>().into()
+    }
+}
+
+impl<T: SIMDField + fmt::Debug> fmt::Debug for AlignedSortedVec<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.as_slice()).finish()
+    }
+}
+
+// padding that sorts after every element in `order`
+fn sentinel<T: SIMDField>(order: Order) -> T {
+    let bits = T::size_in_bits() as u32;
+    match (order, T::is_signed()) {
+        (Order::Ascending, true) => T::unchecked_from_u64(u64::MAX >> (65 - bits)),
+        (Order::Ascending, false) => T::unchecked_from_u64(u64::MAX),
+        (Order::Descending, true) => T::unchecked_from_u64(1 << (bits - 1)),
+        (Order::Descending, false) => T::zero(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::aligned::AlignedSortedVec;
+    use crate::simd::{Order, SIMDField, Side};
+
+    fn check_aligned<T: SIMDField + std::fmt::Debug>(nums: &[T], probes: &[T]) {
+        let aligned = AlignedSortedVec::from(nums.to_vec());
+        assert_eq!(aligned.len(), nums.len());
+        assert_eq!(aligned.as_slice(), nums);
+        assert_eq!(aligned.as_slice().as_ptr() as usize % 64, 0);
+        for p in probes {
+            let left = nums.partition_point(|n| n < p);
+            let right = nums.partition_point(|n| n <= p);
+            assert_eq!(aligned.rank(*p, Side::Left), left);
+            assert_eq!(aligned.rank(*p, Side::Right), right);
+            let hit = (left < right).then_some(left);
+            assert_eq!(aligned.search(*p), hit, "{:?}", p);
+            assert_eq!(aligned.contains(*p), hit.is_some());
+        }
+        assert_eq!(Vec::from(aligned), nums);
+
+        let rev = nums.iter().rev().copied().collect::<Vec<_>>();
+        let aligned = AlignedSortedVec::from_ordered(nums.to_vec(), Order::Descending);
+        assert_eq!(aligned.order(), Order::Descending);
+        assert_eq!(aligned.as_slice(), rev);
+        for p in probes {
+            let left = rev.partition_point(|n| n > p);
+            let right = rev.partition_point(|n| n >= p);
+            assert_eq!(aligned.rank(*p, Side::Left), left);
+            assert_eq!(aligned.rank(*p, Side::Right), right);
+            let hit = (left < right).then_some(left);
+            assert_eq!(aligned.search(*p), hit, "{:?}", p);
+        }
+    }
+
+    #[test]
+    fn test_u8s_aligned() {
+        let probes = (0..=u8::MAX).collect::<Vec<_>>();
+        for size in 0..=u8::MAX {
+            let nums = (0..size).map(|i| i / 3 * 3 + 1).collect::<Vec<_>>();
+            check_aligned(&nums, &probes);
+        }
+        check_aligned(&[0, 5, u8::MAX - 1], &probes);
+        check_aligned(&[u8::MAX; 3], &probes);
+        check_aligned(&[0; 3], &probes);
+    }
+
+    #[test]
+    fn test_i8s_aligned() {
+        let probes = (i8::MIN..=i8::MAX).collect::<Vec<_>>();
+        for size in 0..100i8 {
+            let nums = (0..size).map(|i| i - 99 + i).collect::<Vec<_>>();
+            check_aligned(&nums, &probes);
+        }
+        check_aligned(&[i8::MIN, -1, i8::MAX], &probes);
+    }
+
+    #[test]
+    fn test_u16s_aligned() {
+        let probes = (0..3000u16)
+            .chain([u16::MAX - 1, u16::MAX])
+            .collect::<Vec<_>>();
+        for size in [0u16, 1, 7, 31, 32, 33, 100, 1000] {
+            let nums = (0..size).map(|i| i / 2 * 5).collect::<Vec<_>>();
+            check_aligned(&nums, &probes);
+        }
+    }
+
+    #[test]
+    fn test_i32s_aligned() {
+        let probes = (-2000..2000i32)
+            .chain([i32::MIN, i32::MAX])
+            .collect::<Vec<_>>();
+        for size in [0i32, 1, 15, 16, 17, 500] {
+            let nums = (0..size).map(|i| i * 3 - 700).collect::<Vec<_>>();
+            check_aligned(&nums, &probes);
+        }
+        check_aligned(&[i32::MIN, 0, i32::MAX, i32::MAX], &probes);
+        check_aligned(&[i32::MIN, i32::MIN, 0, i32::MAX], &probes);
+    }
+
+    #[test]
+    fn test_u64s_aligned() {
+        let probes = (0..3000u64)
+            .map(|i| i << 40)
+            .chain([u64::MAX])
+            .collect::<Vec<_>>();
+        for size in [0u64, 1, 7, 8, 9, 1000] {
+            let nums = (0..size).map(|i| (i * 3) << 40).collect::<Vec<_>>();
+            check_aligned(&nums, &probes);
+        }
+        check_aligned(&[1, u64::MAX], &[0, 1, 2, u64::MAX - 1, u64::MAX]);
+        check_aligned(&[0, 1, u64::MAX], &[0, 1, 2, u64::MAX - 1, u64::MAX]);
+    }
+
+    #[test]
+    fn test_aligned_conversions() {
+        let aligned = vec![5i64, -3, 9, 0]
+            .into_iter()
+            .collect::<AlignedSortedVec<_>>();
+        assert_eq!(aligned.to_vec(), [-3, 0, 5, 9]);
+        assert_eq!(aligned.get(3), Some(9));
+        assert_eq!(aligned.get(4), None);
+        assert_eq!(format!("{:?}", aligned), "[-3, 0, 5, 9]");
+        let empty = AlignedSortedVec::<u32>::from(Vec::new());
+        assert!(empty.is_empty());
+        assert_eq!(empty.search(0), None);
+        assert_eq!(empty.rank(7, Side::Right), 0);
+        let desc = AlignedSortedVec::from_ordered(vec![5i64, -3, 9, 0], Order::Descending);
+        assert_eq!(desc.to_vec(), [9, 5, 0, -3]);
+        assert_eq!(desc.search(0), Some(2));
+        assert_eq!(desc.rank(1, Side::Left), 2);
+    }
+}
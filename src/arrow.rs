@@ -0,0 +1,350 @@
+//! search over Arrow primitive arrays without copying their values out
+//!
+//! sorted searches expect the nulls grouped at one end of the array as described by
+//! `NullOrder`, they don't check it, `is_sorted` does once per array; scans skip null slots by
+//! ANDing the validity bitmap into the compare masks
+
+use arrow_array::{Array, ArrowPrimitiveType, PrimitiveArray};
+
+use crate::simd::masked::{
+    count_in_range_valid, count_valid, find_first_in_range_valid, linear_search_valid, Bitmap,
+};
+use crate::simd::{self, SIMDField, Side};
+
+/// where the nulls of a sorted array sit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NullOrder {
+    /// all nulls before the first value
+    First,
+    /// all nulls after the last value
+    Last,
+}
+
+/// index of the first slot equal to `target` in the sorted `array`
+pub fn search<A>(array: &PrimitiveArray<A>, target: A::Native, nulls: NullOrder) -> Option<usize>
+where
+    A: ArrowPrimitiveType,
+    A::Native: SIMDField,
+{
+    let (from, values) = sorted_values(array, nulls);
+    let mut rank = [0];
+    simd::searchsorted(values, &[target], Side::Left, &mut rank);
+    match values.get(rank[0]) {
+        Some(n) if *n == target => Some(from + rank[0]),
+        _ => None,
+    }
+}
+
+/// `searchsorted` over the values of the sorted `array`, `out[j]` is an index into `array`
+///
+/// with `NullOrder::First` the insertion points start after the nulls, with `NullOrder::Last`
+/// they end before them
+///
+/// panics if `out` and `targets` differ in length
+pub fn searchsorted<A>(
+    array: &PrimitiveArray<A>,
+    targets: &[A::Native],
+    side: Side,
+    nulls: NullOrder,
+    out: &mut [usize],
+) where
+    A: ArrowPrimitiveType,
+    A::Native: SIMDField,
+{
+    let (from, values) = sorted_values(array, nulls);
+    simd::searchsorted(values, targets, side, out);
+    if from > 0 {
+        out.iter_mut().for_each(|i| *i += from);
+    }
+}
+
+/// first valid slot equal to `target`, `array` doesn't need to be sorted
+pub fn linear_search<A>(array: &PrimitiveArray<A>, target: A::Native) -> Option<usize>
+where
+    A: ArrowPrimitiveType,
+    A::Native: SIMDField,
+{
+    match validity(array) {
+        Some(valid) => linear_search_valid(array.values(), target, valid),
+        None => simd::linear_search(array.values(), target),
+    }
+}
+
+/// number of valid slots equal to `target`
+pub fn count<A>(array: &PrimitiveArray<A>, target: A::Native) -> usize
+where
+    A: ArrowPrimitiveType,
+    A::Native: SIMDField,
+{
+    match validity(array) {
+        Some(valid) => count_valid(array.values(), target, valid),
+        None => simd::count(array.values(), target),
+    }
+}
+
+/// first valid slot with `lo <= x < hi`
+pub fn find_first_in_range<A>(
+    array: &PrimitiveArray<A>,
+    lo: A::Native,
+    hi: A::Native,
+) -> Option<usize>
+where
+    A: ArrowPrimitiveType,
+    A::Native: SIMDField,
+{
+    match validity(array) {
+        Some(valid) => find_first_in_range_valid(array.values(), lo, hi, valid),
+        None => simd::find_first_in_range(array.values(), lo, hi),
+    }
+}
+
+/// number of valid slots with `lo <= x < hi`
+pub fn count_in_range<A>(array: &PrimitiveArray<A>, lo: A::Native, hi: A::Native) -> usize
+where
+    A: ArrowPrimitiveType,
+    A::Native: SIMDField,
+{
+    match validity(array) {
+        Some(valid) => count_in_range_valid(array.values(), lo, hi, valid),
+        None => simd::count_in_range(array.values(), lo, hi),
+    }
+}
+
+/// whether the nulls of `array` are grouped as `nulls` and its values sorted ascending, the
+/// layout `search` and `searchsorted` expect
+///
+/// O(n), meant to run once per array rather than before every search
+pub fn is_sorted<A>(array: &PrimitiveArray<A>, nulls: NullOrder) -> bool
+where
+    A: ArrowPrimitiveType,
+    A::Native: SIMDField,
+{
+    let nulls_count = array.null_count();
+    let grouped = (0..array.len()).all(|i| {
+        array.is_null(i)
+            == match nulls {
+                NullOrder::First => i < nulls_count,
+                NullOrder::Last => i >= array.len() - nulls_count,
+            }
+    });
+    let (_, values) = sorted_values(array, nulls);
+    grouped && values.windows(2).all(|w| w[0] <= w[1])
+}
+
+// the non-null values of a sorted array and the index of the first of them
+#[inline(always)]
+fn sorted_values<A: ArrowPrimitiveType>(
+    array: &PrimitiveArray<A>,
+    nulls: NullOrder,
+) -> (usize, &[A::Native]) {
+    let values: &[A::Native] = array.values();
+    let nulls_count = array.null_count();
+    match nulls {
+        NullOrder::First => (nulls_count, &values[nulls_count..]),
+        NullOrder::Last => (0, &values[..values.len() - nulls_count]),
+    }
+}
+
+// validity bitmap, `None` when every slot is valid
+#[inline(always)]
+fn validity<A: ArrowPrimitiveType>(array: &PrimitiveArray<A>) -> Option<Bitmap<'_>> {
+    array
+        .nulls()
+        .filter(|nulls| nulls.null_count() > 0)
+        .map(|nulls| Bitmap::new(nulls.inner().values(), nulls.inner().offset()))
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow_array::types::{
+        Int16Type, Int32Type, Int64Type, Int8Type, UInt16Type, UInt32Type, UInt64Type, UInt8Type,
+    };
+    use arrow_array::{Array, ArrowPrimitiveType, PrimitiveArray};
+
+    use crate::arrow::{
+        count, count_in_range, find_first_in_range, is_sorted, linear_search, search, searchsorted,
+        NullOrder,
+    };
+    use crate::simd::{SIMDField, Side};
+
+    fn check_scans<A>(array: &PrimitiveArray<A>, probes: &[A::Native])
+    where
+        A: ArrowPrimitiveType,
+        A::Native: SIMDField + std::fmt::Debug,
+    {
+        let slots = (0..array.len())
+            .map(|i| array.is_valid(i).then(|| array.value(i)))
+            .collect::<Vec<_>>();
+        for p in probes {
+            let hits = slots.iter().enumerate().filter(|(_, s)| **s == Some(*p));
+            assert_eq!(
+                linear_search(array, *p),
+                hits.clone().next().map(|(i, _)| i)
+            );
+            assert_eq!(count(array, *p), hits.count());
+            let two = <A::Native as num::One>::one() + num::One::one();
+            let hi = *p + two;
+            let in_range = |s: &Option<A::Native>| matches!(s, Some(x) if *p <= *x && *x < hi);
+            let hits = slots.iter().enumerate().filter(|(_, s)| in_range(s));
+            assert_eq!(
+                find_first_in_range(array, *p, hi),
+                hits.clone().next().map(|(i, _)| i)
+            );
+            assert_eq!(count_in_range(array, *p, hi), hits.count());
+        }
+    }
+
+    fn check_sorted<A>(values: &[A::Native], nulls: usize, probes: &[A::Native])
+    where
+        A: ArrowPrimitiveType,
+        A::Native: SIMDField + std::fmt::Debug,
+    {
+        for order in [NullOrder::First, NullOrder::Last] {
+            let slots = match order {
+                NullOrder::First => std::iter::repeat_n(None, nulls)
+                    .chain(values.iter().map(|v| Some(*v)))
+                    .collect::<Vec<_>>(),
+                NullOrder::Last => values
+                    .iter()
+                    .map(|v| Some(*v))
+                    .chain(std::iter::repeat_n(None, nulls))
+                    .collect::<Vec<_>>(),
+            };
+            let array = PrimitiveArray::<A>::from_iter(slots.iter().copied());
+            assert!(is_sorted(&array, order));
+            let from = if order == NullOrder::First { nulls } else { 0 };
+            let mut out = vec![0; probes.len()];
+            for side in [Side::Left, Side::Right] {
+                searchsorted(&array, probes, side, order, &mut out);
+                for (p, i) in probes.iter().zip(&out) {
+                    let rank = match side {
+                        Side::Left => values.partition_point(|v| v < p),
+                        Side::Right => values.partition_point(|v| v <= p),
+                    };
+                    assert_eq!(*i, from + rank);
+                }
+            }
+            for p in probes {
+                let rank = values.partition_point(|v| v < p);
+                let expected = (values.get(rank) == Some(p)).then_some(from + rank);
+                assert_eq!(search(&array, *p, order), expected);
+            }
+            check_scans(&array, probes);
+        }
+    }
+
+    #[test]
+    fn test_u8s_arrow() {
+        let values = (0..200u32).map(|i| (i * 5 / 4) as u8).collect::<Vec<_>>();
+        for nulls in [0, 1, 7, 40] {
+            check_sorted::<UInt8Type>(&values, nulls, &(0..u8::MAX - 1).collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    fn test_i8s_arrow() {
+        let values = (-100..100i8).collect::<Vec<_>>();
+        check_sorted::<Int8Type>(&values, 13, &(i8::MIN..i8::MAX - 1).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_i16s_arrow() {
+        let values = (0..500i16).map(|i| i / 2 * 3 - 300).collect::<Vec<_>>();
+        check_sorted::<Int16Type>(&values, 33, &(-400..500).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_u16s_arrow() {
+        let values = (0..3000u32).map(|i| (i * 21) as u16).collect::<Vec<_>>();
+        for nulls in [0, 9, 100] {
+            check_sorted::<UInt16Type>(&values, nulls, &(0..64_000).step_by(7).collect::<Vec<_>>());
+        }
+        // the slice starts inside the leading nulls, so the bitmap has an offset
+        let array = PrimitiveArray::<UInt16Type>::from_iter(
+            std::iter::repeat_n(None, 20).chain(values.iter().map(|v| Some(*v))),
+        );
+        let sliced = array.slice(13, 2000);
+        assert!(is_sorted(&sliced, NullOrder::First));
+        for p in [0, 21, 22, 20_979, 41_958, u16::MAX] {
+            let expected = values[..1993].iter().position(|v| *v == p).map(|i| i + 7);
+            assert_eq!(search(&sliced, p, NullOrder::First), expected);
+        }
+        check_scans(&sliced, &(0..42_000).step_by(5).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_i32s_arrow() {
+        let values = (0..2000i32)
+            .map(|i| i / 3 * 1000 - 300_000)
+            .collect::<Vec<_>>();
+        let probes = (-301_000..370_000).step_by(250).collect::<Vec<_>>();
+        check_sorted::<Int32Type>(&values, 17, &probes);
+        check_sorted::<Int32Type>(
+            &[i32::MIN, -1, 0, i32::MAX],
+            2,
+            &[i32::MIN, -1, 0, 1, i32::MAX - 2],
+        );
+        // trailing nulls cut off by the slice, with a leading offset into the values
+        let array = PrimitiveArray::<Int32Type>::from_iter(
+            values
+                .iter()
+                .map(|v| Some(*v))
+                .chain(std::iter::repeat_n(None, 30)),
+        );
+        let sliced = array.slice(5, 2010);
+        assert!(is_sorted(&sliced, NullOrder::Last));
+        let mut out = vec![0; probes.len()];
+        searchsorted(&sliced, &probes, Side::Left, NullOrder::Last, &mut out);
+        for (p, i) in probes.iter().zip(&out) {
+            assert_eq!(*i, values[5..].partition_point(|v| v < p));
+        }
+        check_scans(&sliced, &probes);
+    }
+
+    #[test]
+    fn test_u32s_arrow() {
+        let values = (0..1000u32).map(|i| i * 3 + (1 << 31)).collect::<Vec<_>>();
+        let probes = (0..3010u32).map(|i| i + (1 << 31)).collect::<Vec<_>>();
+        check_sorted::<UInt32Type>(&values, 65, &probes);
+    }
+
+    #[test]
+    fn test_i64s_arrow() {
+        let values = (0..300i64).map(|i| i * 2 - 300).collect::<Vec<_>>();
+        check_sorted::<Int64Type>(&values, 3, &(-310..310).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_arrow_scattered_nulls() {
+        // nulls everywhere and a sliced array, so the bitmap starts mid byte
+        let slots = (0..1000u64)
+            .map(|i| (i % 5 != 2).then_some(i % 17))
+            .collect::<Vec<_>>();
+        let array = PrimitiveArray::<UInt64Type>::from_iter(slots.iter().copied());
+        check_scans(&array, &(0..20).collect::<Vec<_>>());
+        let sliced = array.slice(3, 900);
+        check_scans(&sliced, &(0..20).collect::<Vec<_>>());
+        // null slots keep whatever value sits under them, they must not match
+        let zeros =
+            PrimitiveArray::<UInt64Type>::from_iter((0..100).map(|i| (i % 2 == 0).then_some(1)));
+        assert_eq!(linear_search(&zeros, 0), None);
+        assert_eq!(count(&zeros, 0), 0);
+        assert_eq!(count(&zeros, 1), 50);
+    }
+
+    #[test]
+    fn test_arrow_is_sorted() {
+        let array = PrimitiveArray::<Int16Type>::from_iter([None, Some(-3), Some(0), Some(0)]);
+        assert!(is_sorted(&array, NullOrder::First));
+        assert!(!is_sorted(&array, NullOrder::Last));
+        let array = PrimitiveArray::<Int16Type>::from_iter([Some(1), None, Some(2)]);
+        assert!(!is_sorted(&array, NullOrder::First));
+        assert!(!is_sorted(&array, NullOrder::Last));
+        let array = PrimitiveArray::<UInt8Type>::from_iter([Some(2), Some(1), None]);
+        assert!(!is_sorted(&array, NullOrder::Last));
+        let array = PrimitiveArray::<UInt8Type>::from_iter([None, None]);
+        assert!(is_sorted(&array, NullOrder::Last));
+        let array = PrimitiveArray::<UInt8Type>::from(Vec::<u8>::new());
+        assert!(is_sorted(&array, NullOrder::First));
+    }
+}
@@ -0,0 +1,328 @@
+//! `bst`: build and query sorted binary files from the command line
+
+use std::error::Error;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use bst_rs::{
+    binary_search_ordered, read_type_tag, searchsorted_ordered, type_tag, write_sorted_array,
+    MmapSortedArray, Order, SIMDField, Side,
+};
+use clap::{Parser, Subcommand, ValueEnum};
+
+type Result<T> = std::result::Result<T, Box<dyn Error>>;
+
+#[derive(Parser)]
+#[command(name = "bst", about = "build and query sorted binary files")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// sort integers from a text or CSV file (`-` for stdin) into a binary file
+    Build {
+        input: PathBuf,
+        output: PathBuf,
+        /// element type of the binary file
+        #[arg(long = "type", value_enum, default_value = "u64")]
+        elem: ElemType,
+        /// 0-based column to read, every line is one value when omitted
+        #[arg(long)]
+        column: Option<usize>,
+        #[arg(long, default_value_t = ',')]
+        delimiter: char,
+        /// skip the first line
+        #[arg(long)]
+        header: bool,
+    },
+    /// print the index of every key, keys are read from stdin when none are given
+    Lookup { file: PathBuf, keys: Vec<String> },
+    /// print the index range `[from, to)` of the elements in `[lo, hi)`
+    Range {
+        file: PathBuf,
+        lo: String,
+        hi: String,
+    },
+    /// print how many times every key occurs
+    Count { file: PathBuf, keys: Vec<String> },
+    /// check the header, checksum and sortedness of a file
+    Verify { file: PathBuf },
+    /// time the search kernels on the file and report ns per lookup
+    Bench {
+        file: PathBuf,
+        #[arg(long, default_value_t = 1_000_000)]
+        queries: usize,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum ElemType {
+    U8,
+    I8,
+    U16,
+    I16,
+    U32,
+    I32,
+    U64,
+    I64,
+}
+
+// call `$f::<T>($args)` with `T` matching the type tag
+macro_rules! dispatch {
+    ($tag:expr, $f:ident($($arg:expr),*)) => {{
+        let tag = $tag;
+        if tag == type_tag::<u8>() {
+            $f::<u8>($($arg),*)
+        } else if tag == type_tag::<i8>() {
+            $f::<i8>($($arg),*)
+        } else if tag == type_tag::<u16>() {
+            $f::<u16>($($arg),*)
+        } else if tag == type_tag::<i16>() {
+            $f::<i16>($($arg),*)
+        } else if tag == type_tag::<u32>() {
+            $f::<u32>($($arg),*)
+        } else if tag == type_tag::<i32>() {
+            $f::<i32>($($arg),*)
+        } else if tag == type_tag::<u64>() {
+            $f::<u64>($($arg),*)
+        } else if tag == type_tag::<i64>() {
+            $f::<i64>($($arg),*)
+        } else {
+            Err(format!("unknown type tag {:#04x}", tag).into())
+        }
+    }};
+}
+
+fn main() {
+    if let Err(e) = run(Cli::parse().command) {
+        eprintln!("bst: {}", e);
+        std::process::exit(1);
+    }
+}
+
+fn run(command: Command) -> Result<()> {
+    match command {
+        Command::Build {
+            input,
+            output,
+            elem,
+            column,
+            delimiter,
+            header,
+        } => {
+            let tag = match elem {
+                ElemType::U8 => type_tag::<u8>(),
+                ElemType::I8 => type_tag::<i8>(),
+                ElemType::U16 => type_tag::<u16>(),
+                ElemType::I16 => type_tag::<i16>(),
+                ElemType::U32 => type_tag::<u32>(),
+                ElemType::I32 => type_tag::<i32>(),
+                ElemType::U64 => type_tag::<u64>(),
+                ElemType::I64 => type_tag::<i64>(),
+            };
+            let source = Source {
+                column,
+                delimiter,
+                header,
+            };
+            dispatch!(tag, build(&input, &output, &source))
+        }
+        Command::Lookup { file, keys } => dispatch!(read_type_tag(&file)?, lookup(&file, &keys)),
+        Command::Range { file, lo, hi } => dispatch!(read_type_tag(&file)?, range(&file, &lo, &hi)),
+        Command::Count { file, keys } => dispatch!(read_type_tag(&file)?, count(&file, &keys)),
+        Command::Verify { file } => dispatch!(read_type_tag(&file)?, verify(&file)),
+        Command::Bench { file, queries } => dispatch!(read_type_tag(&file)?, bench(&file, queries)),
+    }
+}
+
+struct Source {
+    column: Option<usize>,
+    delimiter: char,
+    header: bool,
+}
+
+fn build<T: SIMDField>(input: &Path, output: &Path, source: &Source) -> Result<()> {
+    let reader: Box<dyn BufRead> = if input.as_os_str() == "-" {
+        Box::new(io::stdin().lock())
+    } else {
+        Box::new(BufReader::new(File::open(input)?))
+    };
+    let mut nums = Vec::new();
+    for (i, line) in reader.lines().enumerate().skip(source.header as usize) {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let field = match source.column {
+            Some(c) => line
+                .split(source.delimiter)
+                .nth(c)
+                .ok_or_else(|| format!("line {}: no column {}", i + 1, c))?,
+            None => &line,
+        };
+        nums.push(parse::<T>(field).map_err(|e| format!("line {}: {}", i + 1, e))?);
+    }
+    nums.sort();
+    let mut out = BufWriter::new(File::create(output)?);
+    write_sorted_array(&mut out, &nums)?;
+    out.flush()?;
+    eprintln!("wrote {} elements to {}", nums.len(), output.display());
+    Ok(())
+}
+
+fn lookup<T: SIMDField>(file: &Path, keys: &[String]) -> Result<()> {
+    let array = MmapSortedArray::<T>::open(file)?;
+    let out = io::stdout();
+    let mut out = BufWriter::new(out.lock());
+    for_each_key(keys, |key| {
+        match array.search(parse::<T>(key)?) {
+            Some(i) => writeln!(out, "{}\t{}", key, i)?,
+            None => writeln!(out, "{}\t-", key)?,
+        }
+        Ok(())
+    })?;
+    out.flush()?;
+    Ok(())
+}
+
+fn range<T: SIMDField>(file: &Path, lo: &str, hi: &str) -> Result<()> {
+    let array = MmapSortedArray::<T>::open(file)?;
+    let (lo, hi) = (parse::<T>(lo)?, parse::<T>(hi)?);
+    // descending data holds `[lo, hi)` from the first element below `hi` to the first below `lo`
+    let (keys, side) = match array.order() {
+        Order::Ascending => ([lo, hi], Side::Left),
+        Order::Descending => ([hi, lo], Side::Right),
+    };
+    let mut bounds = [0; 2];
+    searchsorted_ordered(array.as_slice(), &keys, side, array.order(), &mut bounds);
+    let (from, to) = (bounds[0], bounds[1].max(bounds[0]));
+    println!("{}\t{}", from, to);
+    Ok(())
+}
+
+fn count<T: SIMDField>(file: &Path, keys: &[String]) -> Result<()> {
+    let array = MmapSortedArray::<T>::open(file)?;
+    let (nums, order) = (array.as_slice(), array.order());
+    let out = io::stdout();
+    let mut out = BufWriter::new(out.lock());
+    for_each_key(keys, |key| {
+        let key_value = [parse::<T>(key)?];
+        let (mut left, mut right) = ([0], [0]);
+        searchsorted_ordered(nums, &key_value, Side::Left, order, &mut left);
+        searchsorted_ordered(nums, &key_value, Side::Right, order, &mut right);
+        writeln!(out, "{}\t{}", key, right[0] - left[0])?;
+        Ok(())
+    })?;
+    out.flush()?;
+    Ok(())
+}
+
+fn verify<T: SIMDField>(file: &Path) -> Result<()> {
+    // `open` checks the header and the checksum
+    let array = MmapSortedArray::<T>::open(file)?;
+    let (nums, order) = (array.as_slice(), array.order());
+    if let Some(i) = nums.windows(2).position(|w| order.before(w[1], w[0])) {
+        return Err(format!("not sorted at index {}", i + 1).into());
+    }
+    println!(
+        "ok: {} elements of {}{}{}",
+        nums.len(),
+        if T::is_signed() { 'i' } else { 'u' },
+        T::size_in_bits(),
+        if order == Order::Descending {
+            ", descending"
+        } else {
+            ""
+        }
+    );
+    Ok(())
+}
+
+fn bench<T: SIMDField>(file: &Path, queries: usize) -> Result<()> {
+    let array = MmapSortedArray::<T>::open(file)?;
+    let (nums, order) = (array.as_slice(), array.order());
+    if nums.is_empty() {
+        return Err("empty file".into());
+    }
+    // half hits sampled from the file, half arbitrary keys between its ends
+    let (min, max) = match order {
+        Order::Ascending => (nums[0], nums[nums.len() - 1]),
+        Order::Descending => (nums[nums.len() - 1], nums[0]),
+    };
+    let (first, last) = (min.unchecked_u64(), max.unchecked_u64());
+    let span = last.wrapping_sub(first).wrapping_add(1);
+    let mut state = 0x2545F4914F6CDD1Du64;
+    let keys = (0..queries)
+        .map(|i| {
+            state = state
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            let r = state >> 11;
+            if i % 2 == 0 {
+                nums[r as usize % nums.len()]
+            } else if span == 0 {
+                nums[0]
+            } else {
+                T::unchecked_from_u64(first.wrapping_add(r % span))
+            }
+        })
+        .collect::<Vec<_>>();
+
+    report("binary_search_auto", queries, || {
+        keys.iter()
+            .filter(|k| binary_search_ordered(nums, **k, order).is_some())
+            .count()
+    });
+    report("searchsorted", queries, || {
+        let mut ranks = vec![0; keys.len()];
+        searchsorted_ordered(nums, &keys, Side::Left, order, &mut ranks);
+        ranks
+            .iter()
+            .zip(&keys)
+            .filter(|(r, k)| nums.get(**r) == Some(*k))
+            .count()
+    });
+    report("std binary_search", queries, || {
+        keys.iter()
+            .filter(|k| {
+                nums.binary_search_by(|n| match order {
+                    Order::Ascending => n.cmp(k),
+                    Order::Descending => (*k).cmp(n),
+                })
+                .is_ok()
+            })
+            .count()
+    });
+    Ok(())
+}
+
+fn report(name: &str, queries: usize, f: impl Fn() -> usize) {
+    let start = Instant::now();
+    let hits = std::hint::black_box(f());
+    let ns = start.elapsed().as_nanos() as f64 / queries.max(1) as f64;
+    println!("{:<20}{:>10.1} ns/lookup  ({} hits)", name, ns, hits);
+}
+
+fn for_each_key(keys: &[String], mut f: impl FnMut(&str) -> Result<()>) -> Result<()> {
+    if keys.is_empty() {
+        for line in io::stdin().lock().lines() {
+            let line = line?;
+            for key in line.split_whitespace() {
+                f(key)?;
+            }
+        }
+    } else {
+        for key in keys {
+            f(key)?;
+        }
+    }
+    Ok(())
+}
+
+fn parse<T: SIMDField>(s: &str) -> Result<T> {
+    T::from_str_radix(s.trim(), 10).map_err(|_| format!("invalid number `{}`", s.trim()).into())
+}
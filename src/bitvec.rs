@@ -0,0 +1,264 @@
+use crate::simd::{popcount, searchsorted, select_in_word, Side};
+
+/// words covered by one superblock
+const SUPERBLOCK_WORDS: usize = 32;
+
+/// immutable bit vector with O(1) `rank1` and directory guided `select1`/`select0`
+///
+/// the directory has two levels: absolute counts for every superblock of `SUPERBLOCK_WORDS`
+/// words and counts relative to the superblock for every word
+#[derive(Debug, Clone)]
+pub struct BitVector {
+    len: usize,
+    words: Vec<u64>,
+    // ones before every superblock, plus the total as sentinel
+    ones: Vec<u64>,
+    // zeros before every superblock, plus the total as sentinel
+    zeros: Vec<u64>,
+    // ones between the superblock start and every word
+    blocks: Vec<u16>,
+}
+
+impl BitVector {
+    /// `len` bits taken from `words`, LSB first, bits past `len` are ignored
+    ///
+    /// panics if `words` holds less than `len` bits
+    pub fn from_words(mut words: Vec<u64>, len: usize) -> Self {
+        assert!(
+            words.len() * 64 >= len,
+            "BitVector: {} words can't hold {} bits",
+            words.len(),
+            len
+        );
+        words.truncate(len.div_ceil(64));
+        if !len.is_multiple_of(64) {
+            let last = words.len() - 1;
+            words[last] &= (1 << (len % 64)) - 1;
+        }
+        let supers = words.len().div_ceil(SUPERBLOCK_WORDS);
+        let mut ones = Vec::with_capacity(supers + 1);
+        let mut zeros = Vec::with_capacity(supers + 1);
+        let mut blocks = Vec::with_capacity(words.len());
+        let mut total = 0;
+        for (s, superblock) in words.chunks(SUPERBLOCK_WORDS).enumerate() {
+            ones.push(total);
+            zeros.push((s * SUPERBLOCK_WORDS * 64) as u64 - total);
+            let mut relative = 0;
+            for w in superblock {
+                blocks.push(relative);
+                relative += w.count_ones() as u16;
+            }
+            total += popcount(superblock);
+        }
+        ones.push(total);
+        zeros.push(len as u64 - total);
+        BitVector {
+            len,
+            words,
+            ones,
+            zeros,
+            blocks,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn words(&self) -> &[u64] {
+        &self.words
+    }
+
+    /// payload and directory in bytes, excluding the struct itself
+    pub fn size_in_bytes(&self) -> usize {
+        (self.words.len() + self.ones.len() + self.zeros.len()) * 8 + self.blocks.len() * 2
+    }
+
+    pub fn count_ones(&self) -> usize {
+        self.ones[self.ones.len() - 1] as usize
+    }
+
+    pub fn count_zeros(&self) -> usize {
+        self.len - self.count_ones()
+    }
+
+    /// panics if `i >= len`
+    #[inline(always)]
+    pub fn get(&self, i: usize) -> bool {
+        assert!(i < self.len, "BitVector: index {} out of {}", i, self.len);
+        self.words[i / 64] >> (i % 64) & 1 == 1
+    }
+
+    /// number of ones in `[0, i)`, panics if `i > len`
+    #[inline]
+    pub fn rank1(&self, i: usize) -> usize {
+        assert!(i <= self.len, "BitVector: rank {} out of {}", i, self.len);
+        if i == self.len {
+            return self.count_ones();
+        }
+        let word = i / 64;
+        let partial = self.words[word] & ((1 << (i % 64)) - 1);
+        self.ones[word / SUPERBLOCK_WORDS] as usize
+            + self.blocks[word] as usize
+            + partial.count_ones() as usize
+    }
+
+    /// number of zeros in `[0, i)`, panics if `i > len`
+    #[inline]
+    pub fn rank0(&self, i: usize) -> usize {
+        i - self.rank1(i)
+    }
+
+    /// position of the `k`-th (0-based) one
+    pub fn select1(&self, k: usize) -> Option<usize> {
+        if k >= self.count_ones() {
+            return None;
+        }
+        let superblock = self.superblock_of(&self.ones, k);
+        let rest = k - self.ones[superblock] as usize;
+        let (from, to) = self.superblock_words(superblock);
+        let word = from + self.word_of(&self.blocks[from..to], rest);
+        let rest = rest - self.blocks[word] as usize;
+        Some(word * 64 + select_in_word(self.words[word], rest as u32)? as usize)
+    }
+
+    /// position of the `k`-th (0-based) zero
+    pub fn select0(&self, k: usize) -> Option<usize> {
+        if k >= self.count_zeros() {
+            return None;
+        }
+        let superblock = self.superblock_of(&self.zeros, k);
+        let rest = k - self.zeros[superblock] as usize;
+        let (from, to) = self.superblock_words(superblock);
+        let mut blocks = [0u16; SUPERBLOCK_WORDS];
+        for (j, zeros) in blocks[..to - from].iter_mut().enumerate() {
+            *zeros = (j * 64) as u16 - self.blocks[from + j];
+        }
+        let word = from + self.word_of(&blocks[..to - from], rest);
+        let rest = rest - blocks[word - from] as usize;
+        Some(word * 64 + select_in_word(!self.words[word], rest as u32)? as usize)
+    }
+
+    // last superblock starting with at most `k` of the counted bits
+    #[inline(always)]
+    fn superblock_of(&self, counts: &[u64], k: usize) -> usize {
+        let mut superblock = [0];
+        searchsorted(counts, &[k as u64], Side::Right, &mut superblock);
+        superblock[0] - 1
+    }
+
+    #[inline(always)]
+    fn superblock_words(&self, superblock: usize) -> (usize, usize) {
+        let from = superblock * SUPERBLOCK_WORDS;
+        (from, (from + SUPERBLOCK_WORDS).min(self.words.len()))
+    }
+
+    // last word starting with at most `k` of the counted bits
+    #[inline(always)]
+    fn word_of(&self, blocks: &[u16], k: usize) -> usize {
+        let mut word = [0];
+        searchsorted(blocks, &[k as u16], Side::Right, &mut word);
+        word[0] - 1
+    }
+}
+
+impl FromIterator<bool> for BitVector {
+    fn from_iter<I: IntoIterator<Item = bool>>(iter: I) -> Self {
+        let mut words = Vec::new();
+        let mut len = 0;
+        for bit in iter {
+            if len % 64 == 0 {
+                words.push(0);
+            }
+            if bit {
+                words[len / 64] |= 1 << (len % 64);
+            }
+            len += 1;
+        }
+        BitVector::from_words(words, len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bitvec::BitVector;
+
+    fn check_bitvec(bits: &[bool]) {
+        let bv = bits.iter().copied().collect::<BitVector>();
+        assert_eq!(bv.len(), bits.len());
+        let ones = bits
+            .iter()
+            .enumerate()
+            .filter(|(_, b)| **b)
+            .map(|(i, _)| i)
+            .collect::<Vec<_>>();
+        let zeros = bits
+            .iter()
+            .enumerate()
+            .filter(|(_, b)| !**b)
+            .map(|(i, _)| i)
+            .collect::<Vec<_>>();
+        assert_eq!(bv.count_ones(), ones.len());
+        assert_eq!(bv.count_zeros(), zeros.len());
+        let mut rank = 0;
+        for (i, bit) in bits.iter().enumerate() {
+            assert_eq!(bv.get(i), *bit);
+            assert_eq!(bv.rank1(i), rank);
+            assert_eq!(bv.rank0(i), i - rank);
+            rank += *bit as usize;
+        }
+        assert_eq!(bv.rank1(bits.len()), rank);
+        for (k, pos) in ones.iter().enumerate() {
+            assert_eq!(bv.select1(k), Some(*pos));
+        }
+        assert_eq!(bv.select1(ones.len()), None);
+        for (k, pos) in zeros.iter().enumerate() {
+            assert_eq!(bv.select0(k), Some(*pos));
+        }
+        assert_eq!(bv.select0(zeros.len()), None);
+    }
+
+    fn lcg_bits(len: usize, seed: u64, density: u64) -> Vec<bool> {
+        let mut state = seed;
+        (0..len)
+            .map(|_| {
+                state = state
+                    .wrapping_mul(6364136223846793005)
+                    .wrapping_add(1442695040888963407);
+                (state >> 33) % 100 < density
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_bitvec_small() {
+        for len in 0..200 {
+            check_bitvec(&lcg_bits(len, len as u64, 50));
+        }
+        check_bitvec(&[true; 130]);
+        check_bitvec(&[false; 130]);
+    }
+
+    #[test]
+    fn test_bitvec_large() {
+        for density in [1, 10, 50, 90, 99] {
+            check_bitvec(&lcg_bits(50_000, density, density));
+        }
+        check_bitvec(&vec![true; 2048 * 3]);
+        check_bitvec(&vec![false; 2048 * 3 + 1]);
+    }
+
+    #[test]
+    fn test_bitvec_from_words() {
+        let bv = BitVector::from_words(vec![u64::MAX, u64::MAX], 70);
+        assert_eq!(bv.count_ones(), 70);
+        assert_eq!(bv.select1(69), Some(69));
+        assert_eq!(bv.select1(70), None);
+        assert_eq!(bv.select0(0), None);
+        assert_eq!(bv.words().len(), 2);
+    }
+}
@@ -0,0 +1,260 @@
+use std::marker::PhantomData;
+
+use crate::bitvec::BitVector;
+use crate::packed::{pack, unpack};
+use crate::simd::SIMDField;
+
+/// Elias-Fano encoding of a monotone sequence of unsigned integers
+///
+/// the low `low_bits` of every element are stored verbatim, the high part in unary: element `i`
+/// sets bit `(x >> low_bits) + i` of `upper`
+#[derive(Debug, Clone)]
+pub struct EliasFano<T> {
+    len: usize,
+    low_bits: u32,
+    lower: Vec<u64>,
+    upper: BitVector,
+    // `x >> low_bits` of the last element
+    max_high: u64,
+    _marker: PhantomData<T>,
+}
+
+impl<T: SIMDField> EliasFano<T> {
+    /// panics if `T` is signed or `nums` is not sorted ascending
+    pub fn new(nums: &[T]) -> Self {
+        assert!(!T::is_signed(), "EliasFano: only unsigned integers");
+        assert!(
+            nums.windows(2).all(|w| w[0] <= w[1]),
+            "EliasFano: input must be sorted"
+        );
+        let len = nums.len();
+        let universe = nums.last().map_or(0, |x| x.unchecked_u64());
+        // floor(log2(universe / len))
+        let low_bits = match universe.checked_div(len as u64) {
+            Some(r) if r > 0 => 63 - r.leading_zeros(),
+            _ => 0,
+        };
+        let low_mask = low_mask(low_bits);
+        let mut lower = Vec::new();
+        pack(
+            nums.iter().map(|x| x.unchecked_u64() & low_mask),
+            low_bits,
+            &mut lower,
+        );
+        let max_high = universe >> low_bits;
+        let upper_bits = len + max_high as usize + 1;
+        let mut upper = vec![0u64; upper_bits.div_ceil(64)];
+        for (i, x) in nums.iter().enumerate() {
+            let pos = (x.unchecked_u64() >> low_bits) as usize + i;
+            upper[pos / 64] |= 1 << (pos % 64);
+        }
+        EliasFano {
+            len,
+            low_bits,
+            lower,
+            upper: BitVector::from_words(upper, upper_bits),
+            max_high,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// encoded footprint in bytes, excluding the struct itself
+    pub fn size_in_bytes(&self) -> usize {
+        self.lower.len() * 8 + self.upper.size_in_bytes()
+    }
+
+    pub fn get(&self, i: usize) -> Option<T> {
+        if i >= self.len {
+            return None;
+        }
+        let high = (self.upper.select1(i)? - i) as u64;
+        Some(T::unchecked_from_u64(high << self.low_bits | self.low(i)))
+    }
+
+    /// index and value of the first element not less than `x`
+    pub fn successor(&self, x: T) -> Option<(usize, T)> {
+        let i = self.bound(x, false);
+        self.get(i).map(|v| (i, v))
+    }
+
+    /// index and value of the last element not greater than `x`
+    pub fn predecessor(&self, x: T) -> Option<(usize, T)> {
+        let i = self.bound(x, true).checked_sub(1)?;
+        self.get(i).map(|v| (i, v))
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            ef: self,
+            word: 0,
+            bits: self.upper.words().first().copied().unwrap_or(0),
+            i: 0,
+        }
+    }
+
+    #[inline(always)]
+    fn low(&self, i: usize) -> u64 {
+        unpack(&self.lower, self.low_bits, i)
+    }
+
+    // number of elements less than `x`, or not greater than `x` when `inclusive`
+    fn bound(&self, x: T, inclusive: bool) -> usize {
+        let x = x.unchecked_u64();
+        let high = x >> self.low_bits;
+        if high > self.max_high {
+            return self.len;
+        }
+        // bucket `high` starts right after the `high`-th zero and ends at the next one
+        let start = match high {
+            0 => 0,
+            h => self.upper.select0(h as usize - 1).unwrap() + 1,
+        };
+        let end = self.upper.select0(high as usize).unwrap();
+        let low = x & low_mask(self.low_bits);
+        // the low parts of a bucket are sorted, bisect them
+        let (mut lo, mut hi) = (start - high as usize, end - high as usize);
+        while lo < hi {
+            let mid = lo + (hi - lo) / 2;
+            let l = self.low(mid);
+            if l < low || (l == low && inclusive) {
+                lo = mid + 1;
+            } else {
+                hi = mid;
+            }
+        }
+        lo
+    }
+}
+
+pub struct Iter<'a, T> {
+    ef: &'a EliasFano<T>,
+    word: usize,
+    // unvisited ones of `upper[word]`
+    bits: u64,
+    i: usize,
+}
+
+impl<T: SIMDField> Iterator for Iter<'_, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        if self.i >= self.ef.len {
+            return None;
+        }
+        while self.bits == 0 {
+            self.word += 1;
+            self.bits = self.ef.upper.words()[self.word];
+        }
+        let pos = self.word * 64 + self.bits.trailing_zeros() as usize;
+        self.bits &= self.bits - 1;
+        let high = (pos - self.i) as u64;
+        let x = high << self.ef.low_bits | self.ef.low(self.i);
+        self.i += 1;
+        Some(T::unchecked_from_u64(x))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let rest = self.ef.len - self.i;
+        (rest, Some(rest))
+    }
+}
+
+impl<'a, T: SIMDField> IntoIterator for &'a EliasFano<T> {
+    type Item = T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
+#[inline(always)]
+fn low_mask(bits: u32) -> u64 {
+    if bits == 0 {
+        0
+    } else {
+        u64::MAX >> (64 - bits)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::elias_fano::EliasFano;
+    use crate::{binary_search_auto, SIMDField};
+
+    fn check_elias_fano<T: SIMDField + std::fmt::Debug>(nums: &[T], probes: &[T]) {
+        let ef = EliasFano::new(nums);
+        assert_eq!(ef.len(), nums.len());
+        assert_eq!(ef.iter().collect::<Vec<_>>(), nums);
+        for (i, n) in nums.iter().enumerate() {
+            assert_eq!(ef.get(i), Some(*n));
+        }
+        assert_eq!(ef.get(nums.len()), None);
+        for p in probes {
+            let lo = nums.partition_point(|n| n < p);
+            assert_eq!(ef.successor(*p), nums.get(lo).map(|n| (lo, *n)));
+            let hi = nums.partition_point(|n| n <= p);
+            let pred = hi.checked_sub(1).map(|i| (i, nums[i]));
+            assert_eq!(ef.predecessor(*p), pred);
+            if !nums.is_empty() {
+                let hit = ef.successor(*p).filter(|(_, n)| n == p);
+                assert_eq!(hit.map(|(i, _)| i), binary_search_auto(nums, *p));
+            }
+        }
+    }
+
+    #[test]
+    fn test_u32s_elias_fano() {
+        for size in [0u64, 1, 2, 100, 1000, 5000] {
+            let nums = (0..size)
+                .map(|i| (i * i * 171 + i) as u32)
+                .collect::<Vec<_>>();
+            let probes = nums
+                .iter()
+                .flat_map(|n| [n.saturating_sub(1), *n, n + 1])
+                .chain([0, u32::MAX])
+                .collect::<Vec<_>>();
+            check_elias_fano(&nums, &probes);
+        }
+        // a single bucket holding almost every element
+        let nums = (0..4000u32)
+            .map(|i| (1 << 20) + i / 2 * 3)
+            .chain([u32::MAX])
+            .collect::<Vec<_>>();
+        let probes = (0..7000u32).map(|i| (1 << 20) + i).collect::<Vec<_>>();
+        check_elias_fano(&nums, &probes);
+        let nums = [0, 0, 7, 7, 7, 8, 1 << 20, 1 << 20, u32::MAX];
+        check_elias_fano(&nums, &[0, 1, 7, 8, 9, 1 << 20, u32::MAX - 1, u32::MAX]);
+    }
+
+    #[test]
+    fn test_u64s_elias_fano() {
+        for size in [0u64, 1, 2, 100, 1000, 5000] {
+            let nums = (0..size).map(|i| i * i * i * i * 3 + i).collect::<Vec<_>>();
+            let probes = nums
+                .iter()
+                .flat_map(|n| [n.saturating_sub(1), *n, n + 1])
+                .chain([0, u64::MAX])
+                .collect::<Vec<_>>();
+            check_elias_fano(&nums, &probes);
+        }
+        let dense = (0..10_000u64).map(|i| i / 3).collect::<Vec<_>>();
+        check_elias_fano(&dense, &(0..3500).collect::<Vec<_>>());
+        let nums = [1, 1 << 40, 1 << 63, u64::MAX - 1, u64::MAX];
+        check_elias_fano(&nums, &[0, 1, 2, 1 << 40, 1 << 63, u64::MAX]);
+    }
+
+    #[test]
+    fn test_u16s_elias_fano() {
+        let nums = (0..3000u32).map(|i| (i * 21) as u16).collect::<Vec<_>>();
+        check_elias_fano(&nums, &(0..=u16::MAX).step_by(5).collect::<Vec<_>>());
+    }
+}
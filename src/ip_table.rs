@@ -0,0 +1,489 @@
+use std::fmt;
+use std::net::{Ipv4Addr, Ipv6Addr};
+use std::str::FromStr;
+
+use crate::simd::{searchsorted, Side};
+
+/// addresses resolved per `searchsorted` call by `lookup_batch`
+const BATCH_KEYS: usize = 256;
+
+/// an address family `IpLookupTable` can index
+///
+/// addresses are widened to a left aligned `u128` key, so a prefix of length `l` always covers
+/// the top `l` bits of the key
+pub trait IpAddress: Copy {
+    const BITS: u8;
+
+    fn to_key(self) -> u128;
+
+    fn from_key(key: u128) -> Self;
+}
+
+impl IpAddress for Ipv4Addr {
+    const BITS: u8 = 32;
+
+    #[inline(always)]
+    fn to_key(self) -> u128 {
+        (u32::from(self) as u128) << 96
+    }
+
+    #[inline(always)]
+    fn from_key(key: u128) -> Self {
+        Ipv4Addr::from((key >> 96) as u32)
+    }
+}
+
+impl IpAddress for Ipv6Addr {
+    const BITS: u8 = 128;
+
+    #[inline(always)]
+    fn to_key(self) -> u128 {
+        u128::from(self)
+    }
+
+    #[inline(always)]
+    fn from_key(key: u128) -> Self {
+        Ipv6Addr::from(key)
+    }
+}
+
+/// why an `IpLookupTable` can't be built
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IpTableError {
+    /// prefix `index` is longer than the address
+    PrefixLength { index: usize, len: u8 },
+    /// `line` (1-based) of the text input isn't `<address>/<len> <value>`
+    Parse { line: usize },
+}
+
+impl fmt::Display for IpTableError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IpTableError::PrefixLength { index, len } => {
+                write!(
+                    f,
+                    "IpLookupTable: prefix {} has invalid length {}",
+                    index, len
+                )
+            }
+            IpTableError::Parse { line } => {
+                write!(f, "IpLookupTable: can't parse line {}", line)
+            }
+        }
+    }
+}
+
+impl std::error::Error for IpTableError {}
+
+/// longest prefix match over CIDR prefixes
+///
+/// at build time the nested prefixes are flattened into sorted disjoint ranges, each owned by
+/// the longest prefix covering it, so a lookup is a predecessor search over the range starts
+/// plus a check against the range end
+///
+/// IPv4 starts are searched with SIMD as `u32`, IPv6 ones over their high 64 bits, where ties
+/// (only possible with prefixes longer than /64) are settled by a scalar search over the run
+#[derive(Debug, Clone)]
+pub struct IpLookupTable<A, V> {
+    prefixes: Vec<(A, u8, V)>,
+    // every range start of an IPv4 table, empty for IPv6
+    v4_starts: Vec<u32>,
+    // high 64 bits of every range start of an IPv6 table, empty for IPv4
+    highs: Vec<u64>,
+    starts: Vec<u128>,
+    ends: Vec<u128>,
+    // prefix owning every range
+    owners: Vec<usize>,
+}
+
+impl<A: IpAddress, V> IpLookupTable<A, V> {
+    /// host bits of the addresses are ignored, on duplicate prefixes the last one wins
+    pub fn new<I>(prefixes: I) -> Result<Self, IpTableError>
+    where
+        I: IntoIterator<Item = (A, u8, V)>,
+    {
+        let mut prefixes = prefixes
+            .into_iter()
+            .enumerate()
+            .map(|(index, (addr, len, value))| {
+                if len > A::BITS {
+                    Err(IpTableError::PrefixLength { index, len })
+                } else {
+                    Ok((A::from_key(addr.to_key() & net_mask(len)), len, value))
+                }
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        // outer prefixes first, the stable sort keeps duplicates in input order
+        prefixes.sort_by_key(|(addr, len, _)| (addr.to_key(), *len));
+        let mut deduped: Vec<(A, u8, V)> = Vec::with_capacity(prefixes.len());
+        for prefix in prefixes {
+            match deduped.last_mut() {
+                Some(last) if last.0.to_key() == prefix.0.to_key() && last.1 == prefix.1 => {
+                    *last = prefix
+                }
+                _ => deduped.push(prefix),
+            }
+        }
+        let mut table = IpLookupTable {
+            prefixes: deduped,
+            v4_starts: Vec::new(),
+            highs: Vec::new(),
+            starts: Vec::new(),
+            ends: Vec::new(),
+            owners: Vec::new(),
+        };
+        table.flatten();
+        Ok(table)
+    }
+
+    /// number of distinct prefixes
+    pub fn len(&self) -> usize {
+        self.prefixes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.prefixes.is_empty()
+    }
+
+    /// number of disjoint ranges the prefixes flatten into
+    pub fn ranges(&self) -> usize {
+        self.starts.len()
+    }
+
+    /// the longest prefix holding `addr`, as network address, length and value
+    pub fn lookup(&self, addr: A) -> Option<(A, u8, &V)> {
+        let mut rank = [0];
+        self.rank(&[addr], &mut rank);
+        self.covering(rank[0], addr.to_key())
+    }
+
+    /// `out[j]` is the longest prefix holding `addrs[j]`
+    ///
+    /// panics if `out` and `addrs` differ in length
+    pub fn lookup_batch<'a>(&'a self, addrs: &[A], out: &mut [Option<(A, u8, &'a V)>]) {
+        assert_eq!(
+            addrs.len(),
+            out.len(),
+            "IpLookupTable: `out` must be as long as `addrs`"
+        );
+        let mut ranks = [0usize; BATCH_KEYS];
+        for (addrs, out) in addrs.chunks(BATCH_KEYS).zip(out.chunks_mut(BATCH_KEYS)) {
+            let ranks = &mut ranks[..addrs.len()];
+            self.rank(addrs, ranks);
+            for ((rank, addr), prefix) in ranks.iter().zip(addrs).zip(out.iter_mut()) {
+                *prefix = self.covering(*rank, addr.to_key());
+            }
+        }
+    }
+
+    // `ranks[j]` is the number of ranges whose search key isn't above that of `addrs[j]`, at
+    // most `BATCH_KEYS` of them
+    fn rank(&self, addrs: &[A], ranks: &mut [usize]) {
+        if A::BITS <= 32 {
+            let mut keys = [0u32; BATCH_KEYS];
+            let keys = &mut keys[..addrs.len()];
+            for (k, addr) in keys.iter_mut().zip(addrs) {
+                *k = (addr.to_key() >> 96) as u32;
+            }
+            searchsorted(&self.v4_starts, keys, Side::Right, ranks);
+        } else {
+            let mut highs = [0u64; BATCH_KEYS];
+            let highs = &mut highs[..addrs.len()];
+            for (high, addr) in highs.iter_mut().zip(addrs) {
+                *high = (addr.to_key() >> 64) as u64;
+            }
+            searchsorted(&self.highs, highs, Side::Right, ranks);
+        }
+    }
+
+    // `rank` ranges start with high bits not above those of `key`, the range holding `key` is
+    // the last of them starting at or before `key`
+    #[inline(always)]
+    fn covering(&self, rank: usize, key: u128) -> Option<(A, u8, &V)> {
+        let mut i = rank.checked_sub(1)?;
+        if self.starts[i] > key {
+            // same high bits, settle the run by the full key
+            let high = (key >> 64) as u64;
+            let run = self.highs[..i].partition_point(|h| *h < high);
+            i = (run + self.starts[run..i].partition_point(|s| *s <= key)).checked_sub(1)?;
+        }
+        if key > self.ends[i] {
+            return None;
+        }
+        let (addr, len, value) = &self.prefixes[self.owners[i]];
+        Some((*addr, *len, value))
+    }
+
+    // sweep the sorted prefixes with a stack of the ones enclosing the current position, the
+    // innermost of them owns everything up to the next nested prefix or its own end
+    fn flatten(&mut self) {
+        let mut stack: Vec<usize> = Vec::new();
+        // next key not yet assigned to a range, `None` once past `u128::MAX`
+        let mut pos = Some(0u128);
+        for p in 0..self.prefixes.len() {
+            let (start, _) = self.bounds(p);
+            while let Some(&top) = stack.last() {
+                let (_, top_end) = self.bounds(top);
+                if top_end >= start {
+                    break;
+                }
+                self.close(top, top_end, &mut pos);
+                stack.pop();
+            }
+            if let (Some(&top), Some(from)) = (stack.last(), pos) {
+                if from < start {
+                    self.push_range(from, start - 1, top);
+                }
+            }
+            stack.push(p);
+            pos = Some(start);
+        }
+        while let Some(top) = stack.pop() {
+            let (_, top_end) = self.bounds(top);
+            self.close(top, top_end, &mut pos);
+        }
+    }
+
+    // `owner` ends, it takes whatever is left of it
+    fn close(&mut self, owner: usize, end: u128, pos: &mut Option<u128>) {
+        if let Some(from) = *pos {
+            if from <= end {
+                self.push_range(from, end, owner);
+                *pos = end.checked_add(1);
+            }
+        }
+    }
+
+    fn push_range(&mut self, start: u128, end: u128, owner: usize) {
+        if A::BITS <= 32 {
+            self.v4_starts.push((start >> 96) as u32);
+        } else {
+            self.highs.push((start >> 64) as u64);
+        }
+        self.starts.push(start);
+        self.ends.push(end);
+        self.owners.push(owner);
+    }
+
+    #[inline(always)]
+    fn bounds(&self, p: usize) -> (u128, u128) {
+        let (addr, len, _) = &self.prefixes[p];
+        let start = addr.to_key();
+        (start, start | !net_mask(*len))
+    }
+}
+
+impl<A: IpAddress + FromStr> IpLookupTable<A, String> {
+    /// one `<address>/<len> <value>` per line, blank lines and `#` comments are skipped
+    ///
+    /// the value is the rest of the line with surrounding whitespace trimmed
+    pub fn from_text(text: &str) -> Result<Self, IpTableError> {
+        let mut prefixes = Vec::new();
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let parse_error = IpTableError::Parse { line: i + 1 };
+            let (cidr, value) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+            let (addr, len) = cidr.split_once('/').ok_or(parse_error.clone())?;
+            let addr = addr.parse::<A>().map_err(|_| parse_error.clone())?;
+            let len = len.parse::<u8>().map_err(|_| parse_error.clone())?;
+            if len > A::BITS {
+                return Err(parse_error);
+            }
+            prefixes.push((addr, len, value.trim().to_string()));
+        }
+        IpLookupTable::new(prefixes)
+    }
+}
+
+// top `len` bits set
+#[inline(always)]
+fn net_mask(len: u8) -> u128 {
+    match len {
+        0 => 0,
+        len => u128::MAX << (128 - len as u32),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::{Ipv4Addr, Ipv6Addr};
+
+    use crate::ip_table::{IpAddress, IpLookupTable, IpTableError};
+
+    // binary trie over the key bits, the reference for longest prefix match
+    #[derive(Default)]
+    struct Trie {
+        children: [Option<Box<Trie>>; 2],
+        prefix: Option<usize>,
+    }
+
+    impl Trie {
+        fn insert(&mut self, key: u128, len: u8, prefix: usize) {
+            let mut node = self;
+            for b in 0..len {
+                let bit = (key >> (127 - b)) as usize & 1;
+                node = node.children[bit].get_or_insert_with(Default::default);
+            }
+            node.prefix = Some(prefix);
+        }
+
+        fn lookup(&self, key: u128) -> Option<usize> {
+            let mut node = self;
+            let mut best = node.prefix;
+            for b in 0..128 {
+                let bit = (key >> (127 - b)) as usize & 1;
+                match &node.children[bit] {
+                    Some(child) => node = child,
+                    None => break,
+                }
+                best = node.prefix.or(best);
+            }
+            best
+        }
+    }
+
+    struct Lcg(u64);
+
+    impl Lcg {
+        fn next(&mut self) -> u64 {
+            self.0 = self
+                .0
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            self.0
+        }
+
+        fn next_u128(&mut self) -> u128 {
+            (self.next() as u128) << 64 | self.next() as u128
+        }
+    }
+
+    fn check_ip_table<A: IpAddress + std::fmt::Debug + PartialEq>(
+        prefixes: &[(A, u8)],
+        probes: &[A],
+    ) {
+        let table =
+            IpLookupTable::new(prefixes.iter().enumerate().map(|(i, (a, l))| (*a, *l, i))).unwrap();
+        let mut trie = Trie::default();
+        for (i, (addr, len)) in prefixes.iter().enumerate() {
+            trie.insert(addr.to_key(), *len, i);
+        }
+        let mut out = vec![None; probes.len()];
+        table.lookup_batch(probes, &mut out);
+        for (probe, batched) in probes.iter().zip(out) {
+            let found = table.lookup(*probe);
+            assert_eq!(batched, found);
+            assert_eq!(found.map(|(_, _, i)| *i), trie.lookup(probe.to_key()));
+            if let Some((net, len, _)) = found {
+                assert_eq!(net.to_key(), probe.to_key() & super::net_mask(len));
+            }
+        }
+    }
+
+    fn random_prefixes<A: IpAddress>(rng: &mut Lcg, count: usize) -> Vec<(A, u8)> {
+        // a small pool of networks makes sure prefixes nest
+        let pool = (0..8).map(|_| rng.next_u128()).collect::<Vec<_>>();
+        (0..count)
+            .map(|_| {
+                let base = pool[rng.next() as usize % pool.len()];
+                let len = (rng.next() % (A::BITS as u64 + 1)) as u8;
+                let noise = rng.next_u128() >> (rng.next() % 128);
+                (A::from_key(base ^ noise), len)
+            })
+            .collect()
+    }
+
+    fn probes<A: IpAddress>(rng: &mut Lcg, prefixes: &[(A, u8)]) -> Vec<A> {
+        let mut probes = (0..500)
+            .map(|_| A::from_key(rng.next_u128()))
+            .collect::<Vec<_>>();
+        for (addr, len) in prefixes {
+            let start = addr.to_key() & super::net_mask(*len);
+            let end = start | !super::net_mask(*len);
+            let step = 1u128 << (128 - A::BITS as u32);
+            for key in [start, end, start.wrapping_sub(step), end.wrapping_add(step)] {
+                probes.push(A::from_key(key));
+            }
+        }
+        probes
+    }
+
+    #[test]
+    fn test_ipv4_table() {
+        let mut rng = Lcg(4);
+        for count in [0, 1, 2, 10, 100, 1000] {
+            let prefixes = random_prefixes::<Ipv4Addr>(&mut rng, count);
+            let probes = probes(&mut rng, &prefixes);
+            check_ip_table(&prefixes, &probes);
+        }
+    }
+
+    #[test]
+    fn test_ipv6_table() {
+        let mut rng = Lcg(6);
+        for count in [0, 1, 2, 10, 100, 1000] {
+            let prefixes = random_prefixes::<Ipv6Addr>(&mut rng, count);
+            let probes = probes(&mut rng, &prefixes);
+            check_ip_table(&prefixes, &probes);
+        }
+    }
+
+    #[test]
+    fn test_ip_table_text() {
+        let text = "
+            # default route and a few networks
+            0.0.0.0/0 default
+            10.0.0.0/8 private
+            10.1.0.0/16   lab one
+            10.1.2.3/32 host
+
+            192.168.1.77/24 home
+        ";
+        let table = IpLookupTable::<Ipv4Addr, String>::from_text(text).unwrap();
+        assert_eq!(table.len(), 5);
+        let lookup = |addr: [u8; 4]| {
+            table
+                .lookup(Ipv4Addr::from(addr))
+                .map(|(net, len, v)| (net.to_string(), len, v.as_str()))
+        };
+        assert_eq!(lookup([8, 8, 8, 8]), Some(("0.0.0.0".into(), 0, "default")));
+        assert_eq!(
+            lookup([10, 9, 0, 1]),
+            Some(("10.0.0.0".into(), 8, "private"))
+        );
+        assert_eq!(
+            lookup([10, 1, 2, 2]),
+            Some(("10.1.0.0".into(), 16, "lab one"))
+        );
+        assert_eq!(lookup([10, 1, 2, 3]), Some(("10.1.2.3".into(), 32, "host")));
+        assert_eq!(
+            lookup([10, 1, 2, 4]),
+            Some(("10.1.0.0".into(), 16, "lab one"))
+        );
+        assert_eq!(
+            lookup([192, 168, 1, 0]),
+            Some(("192.168.1.0".into(), 24, "home"))
+        );
+        assert_eq!(
+            lookup([255, 255, 255, 255]),
+            Some(("0.0.0.0".into(), 0, "default"))
+        );
+
+        let table = IpLookupTable::<Ipv6Addr, String>::from_text("2001:db8::/32 doc").unwrap();
+        assert!(table.lookup("2001:db8::1".parse().unwrap()).is_some());
+        assert!(table.lookup("2001:db9::1".parse().unwrap()).is_none());
+
+        let bad = IpLookupTable::<Ipv4Addr, String>::from_text("1.2.3.0/24 a\n1.2.3.0/33 b");
+        assert_eq!(bad.unwrap_err(), IpTableError::Parse { line: 2 });
+        let bad = IpLookupTable::<Ipv4Addr, String>::from_text("1.2.3.0 a");
+        assert_eq!(bad.unwrap_err(), IpTableError::Parse { line: 1 });
+        let bad = IpLookupTable::new([(Ipv4Addr::LOCALHOST, 8, ()), (Ipv4Addr::LOCALHOST, 40, ())]);
+        assert_eq!(
+            bad.unwrap_err(),
+            IpTableError::PrefixLength { index: 1, len: 40 }
+        );
+    }
+}
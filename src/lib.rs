@@ -1,18 +1,60 @@
-pub use crate::simd::SIMDField;
+pub use crate::aligned::AlignedSortedVec;
+pub use crate::bitvec::BitVector;
+pub use crate::elias_fano::EliasFano;
+pub use crate::ip_table::{IpAddress, IpLookupTable, IpTableError};
+#[cfg(feature = "mmap")]
+pub use crate::mmap::{
+    read_type_tag, type_tag, write_sorted_array, write_sorted_array_ordered, MmapError,
+    MmapSortedArray,
+};
+pub use crate::packed::PackedSortedArray;
+#[cfg(feature = "rayon")]
+pub use crate::parallel::{par_linear_search, par_search_batch};
+pub use crate::range_map::{RangeMap, RangeMapError};
+pub use crate::simd::{
+    ceil, ceil_ordered, count, count_in_range, filter_range, filter_range_values, find_all,
+    find_first_in_range, floor, floor_ordered, histogram, linear_search, linear_search_any,
+    linear_search_generic, nearest, nearest_ordered, neighbors, neighbors_ordered, popcount,
+    rlinear_search, search_array, searchsorted, searchsorted_ordered, select_in_word, Order,
+    SIMDField, Side, Tie, MAX_BROADCAST_KEYS, MAX_UNROLLED_BYTES,
+};
+pub use crate::small_set::{SimdSmallSet, SmallSetError, SMALL_SET_BYTES};
 
+mod aligned;
+#[cfg(feature = "arrow")]
+pub mod arrow;
+mod bitvec;
+mod elias_fano;
+mod ip_table;
+#[cfg(feature = "mmap")]
+mod mmap;
+#[cfg(feature = "ndarray")]
+pub mod nd;
+mod packed;
+#[cfg(feature = "rayon")]
+mod parallel;
+mod range_map;
 mod simd;
+mod small_set;
 
 pub fn binary_search_auto<T: SIMDField>(nums: &[T], target: T) -> Option<usize> {
+    binary_search_ordered(nums, target, Order::Ascending)
+}
+
+/// `binary_search_auto` over `nums` sorted in `order`
+pub fn binary_search_ordered<T: SIMDField>(nums: &[T], target: T, order: Order) -> Option<usize> {
     let len = nums.len();
     let field_size = T::size_in_bits();
     let total_size = len as u64 * field_size as u64;
+    // the bisection wins once the slice outgrows 1024 bits, see the README table
     match total_size {
-        total_size if total_size <= 128 * 1024 => simd::linear_search(nums, target),
-        _ => simd::binary_search(nums, target),
+        total_size if total_size <= 1024 => simd::linear_search(nums, target),
+        _ => simd::binary_search(nums, target, order),
     }
 }
 
 #[cfg(test)]
+#[allow(clippy::useless_conversion, clippy::identity_op)]
 mod tests {
     use super::*;
 
@@ -119,4 +161,50 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_descending_bst() {
+        // past the linear scan threshold for every width
+        let i16s = (0..20_000i32)
+            .rev()
+            .map(|i| (i * 3 - 30_000) as i16)
+            .collect::<Vec<_>>();
+        for target in (-30_000..=29_997i16).step_by(7) {
+            let ans = binary_search_ordered(&i16s, target, Order::Descending);
+            assert_eq!(ans, i16s.iter().position(|n| *n == target));
+        }
+        let u64s = (0..40_000u64).rev().map(|i| i * 3).collect::<Vec<_>>();
+        for target in 0..=119_997u64 {
+            let ans = binary_search_ordered(&u64s, target, Order::Descending);
+            let expected = (target % 3 == 0).then(|| 39_999 - target as usize / 3);
+            assert_eq!(ans, expected);
+        }
+    }
+
+    #[test]
+    fn test_descending_bst_out_of_range() {
+        // targets past either end of the slice, and empty slices
+        let i16s = (0..20_000i32)
+            .rev()
+            .map(|i| (i * 3 - 30_000) as i16)
+            .collect::<Vec<_>>();
+        for target in (i16::MIN..-30_000).chain(29_998..=i16::MAX) {
+            assert_eq!(
+                binary_search_ordered(&i16s, target, Order::Descending),
+                None
+            );
+        }
+        let u64s = (0..40_000u64).rev().map(|i| i * 3).collect::<Vec<_>>();
+        for target in (119_998..120_100).chain([u64::MAX]) {
+            assert_eq!(
+                binary_search_ordered(&u64s, target, Order::Descending),
+                None
+            );
+        }
+        assert_eq!(
+            binary_search_ordered::<u64>(&[], 0, Order::Descending),
+            None
+        );
+        assert_eq!(binary_search_ordered::<i8>(&[], -1, Order::Ascending), None);
+    }
 }
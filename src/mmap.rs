@@ -0,0 +1,394 @@
+use std::fmt;
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::marker::PhantomData;
+use std::path::Path;
+
+use memmap2::Mmap;
+
+use crate::binary_search_ordered;
+use crate::simd::{Order, SIMDField};
+
+/// first bytes of every sorted array file
+pub const MAGIC: [u8; 8] = *b"BSTSORT\0";
+
+/// format version written by `write_sorted_array`
+pub const VERSION: u32 = 1;
+
+/// header size, the payload starts right after it and stays 64-byte aligned in the mapping
+pub const HEADER_LEN: usize = 64;
+
+// header layout, all little endian:
+//   0..8   magic
+//   8..12  version
+//   12     type tag, element bytes | 0x80 when signed
+//   13     payload byte order, `BYTE_ORDER_LE` or `BYTE_ORDER_BE`
+//   14     sort order, 0 ascending, 1 descending
+//   15     zero
+//   16..24 element count
+//   24..32 payload offset
+//   32..36 crc32 of the payload
+//   36..64 zero
+
+/// header byte 13 of a payload stored little endian
+pub const BYTE_ORDER_LE: u8 = 0;
+
+/// header byte 13 of a payload stored big endian
+pub const BYTE_ORDER_BE: u8 = 1;
+
+// the payload is searched in place, so it is stored in the byte order of the writer
+const NATIVE_BYTE_ORDER: u8 = if cfg!(target_endian = "big") {
+    BYTE_ORDER_BE
+} else {
+    BYTE_ORDER_LE
+};
+
+/// why a sorted array file can't be opened
+#[derive(Debug)]
+pub enum MmapError {
+    Io(io::Error),
+    /// not a sorted array file
+    Magic,
+    /// written by an unknown format version
+    Version(u32),
+    /// holds elements of another width or signedness
+    TypeTag {
+        expected: u8,
+        found: u8,
+    },
+    /// payload written on a host of the other byte order
+    ByteOrder,
+    /// unknown sort order byte
+    Order(u8),
+    /// header and file size disagree
+    Length,
+    /// payload doesn't match the header checksum
+    Checksum,
+}
+
+impl fmt::Display for MmapError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MmapError::Io(e) => write!(f, "MmapSortedArray: {}", e),
+            MmapError::Magic => write!(f, "MmapSortedArray: bad magic"),
+            MmapError::Version(v) => write!(f, "MmapSortedArray: unsupported version {}", v),
+            MmapError::TypeTag { expected, found } => write!(
+                f,
+                "MmapSortedArray: type tag {:#04x} doesn't match {:#04x}",
+                found, expected
+            ),
+            MmapError::ByteOrder => write!(f, "MmapSortedArray: payload byte order mismatch"),
+            MmapError::Order(o) => write!(f, "MmapSortedArray: unknown sort order {:#04x}", o),
+            MmapError::Length => write!(f, "MmapSortedArray: truncated payload"),
+            MmapError::Checksum => write!(f, "MmapSortedArray: checksum mismatch"),
+        }
+    }
+}
+
+impl std::error::Error for MmapError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            MmapError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for MmapError {
+    fn from(e: io::Error) -> Self {
+        MmapError::Io(e)
+    }
+}
+
+/// tag of `T` in the header
+pub fn type_tag<T: SIMDField>() -> u8 {
+    (T::size_in_bits() / 8) as u8 | if T::is_signed() { 0x80 } else { 0 }
+}
+
+/// type tag of the sorted array file at `path`, to pick `T` before opening it
+pub fn read_type_tag<P: AsRef<Path>>(path: P) -> Result<u8, MmapError> {
+    let mut header = [0u8; HEADER_LEN];
+    File::open(path)?
+        .read_exact(&mut header)
+        .map_err(|_| MmapError::Magic)?;
+    if header[0..8] != MAGIC {
+        return Err(MmapError::Magic);
+    }
+    let version = u32::from_le_bytes(header[8..12].try_into().unwrap());
+    if version != VERSION {
+        return Err(MmapError::Version(version));
+    }
+    Ok(header[12])
+}
+
+/// write the sorted `nums` as header + payload
+///
+/// fails with `InvalidInput` if `nums` is not sorted ascending
+pub fn write_sorted_array<T: SIMDField, W: Write>(out: &mut W, nums: &[T]) -> io::Result<()> {
+    write_sorted_array_ordered(out, nums, Order::Ascending)
+}
+
+/// `write_sorted_array` for `nums` sorted in `order`, which the header records
+pub fn write_sorted_array_ordered<T: SIMDField, W: Write>(
+    out: &mut W,
+    nums: &[T],
+    order: Order,
+) -> io::Result<()> {
+    if nums.windows(2).any(|w| order.before(w[1], w[0])) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "write_sorted_array: input must be sorted",
+        ));
+    }
+    let payload = as_bytes(nums);
+    let mut header = [0u8; HEADER_LEN];
+    header[0..8].copy_from_slice(&MAGIC);
+    header[8..12].copy_from_slice(&VERSION.to_le_bytes());
+    header[12] = type_tag::<T>();
+    header[13] = NATIVE_BYTE_ORDER;
+    header[14] = match order {
+        Order::Ascending => 0,
+        Order::Descending => 1,
+    };
+    header[16..24].copy_from_slice(&(nums.len() as u64).to_le_bytes());
+    header[24..32].copy_from_slice(&(HEADER_LEN as u64).to_le_bytes());
+    header[32..36].copy_from_slice(&crc32fast::hash(payload).to_le_bytes());
+    out.write_all(&header)?;
+    out.write_all(payload)
+}
+
+/// a sorted array file mapped read-only, searched in place without copying
+#[derive(Debug)]
+pub struct MmapSortedArray<T> {
+    map: Mmap,
+    // payload start in `map`
+    offset: usize,
+    len: usize,
+    order: Order,
+    _marker: PhantomData<T>,
+}
+
+impl<T: SIMDField> MmapSortedArray<T> {
+    /// map `path` and validate its header and checksum
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, MmapError> {
+        let file = File::open(path)?;
+        // the mapping is only valid as long as nobody truncates or rewrites the file
+        let map = unsafe { Mmap::map(&file)? };
+        Self::from_mmap(map)
+    }
+
+    /// validate the header and checksum of an existing mapping
+    pub fn from_mmap(map: Mmap) -> Result<Self, MmapError> {
+        let header = map.get(..HEADER_LEN).ok_or(MmapError::Magic)?;
+        if header[0..8] != MAGIC {
+            return Err(MmapError::Magic);
+        }
+        let version = u32::from_le_bytes(header[8..12].try_into().unwrap());
+        if version != VERSION {
+            return Err(MmapError::Version(version));
+        }
+        if header[12] != type_tag::<T>() {
+            return Err(MmapError::TypeTag {
+                expected: type_tag::<T>(),
+                found: header[12],
+            });
+        }
+        if header[13] != NATIVE_BYTE_ORDER {
+            return Err(MmapError::ByteOrder);
+        }
+        let order = match header[14] {
+            0 => Order::Ascending,
+            1 => Order::Descending,
+            o => return Err(MmapError::Order(o)),
+        };
+        let len = u64::from_le_bytes(header[16..24].try_into().unwrap()) as usize;
+        let offset = u64::from_le_bytes(header[24..32].try_into().unwrap()) as usize;
+        let checksum = u32::from_le_bytes(header[32..36].try_into().unwrap());
+        let bytes = len
+            .checked_mul(T::size_in_bits() / 8)
+            .and_then(|n| n.checked_add(offset))
+            .ok_or(MmapError::Length)?;
+        if offset < HEADER_LEN
+            || !offset.is_multiple_of(std::mem::align_of::<T>())
+            || bytes != map.len()
+        {
+            return Err(MmapError::Length);
+        }
+        if crc32fast::hash(&map[offset..]) != checksum {
+            return Err(MmapError::Checksum);
+        }
+        Ok(MmapSortedArray {
+            map,
+            offset,
+            len,
+            order,
+            _marker: PhantomData,
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// order the payload is sorted in, as recorded by the writer
+    pub fn order(&self) -> Order {
+        self.order
+    }
+
+    /// the payload, borrowed straight from the mapping
+    pub fn as_slice(&self) -> &[T] {
+        // the mapping is page aligned, the offset and length were checked by `from_mmap`
+        let payload = self.map[self.offset..].as_ptr() as *const T;
+        unsafe { std::slice::from_raw_parts(payload, self.len) }
+    }
+
+    /// index of an element equal to `target`
+    pub fn search(&self, target: T) -> Option<usize> {
+        binary_search_ordered(self.as_slice(), target, self.order)
+    }
+
+    pub fn contains(&self, target: T) -> bool {
+        self.search(target).is_some()
+    }
+}
+
+fn as_bytes<T: SIMDField>(nums: &[T]) -> &[u8] {
+    unsafe { std::slice::from_raw_parts(nums.as_ptr() as *const u8, std::mem::size_of_val(nums)) }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Seek, SeekFrom, Write};
+
+    use crate::mmap::{
+        read_type_tag, type_tag, write_sorted_array, write_sorted_array_ordered, MmapError,
+        MmapSortedArray, HEADER_LEN,
+    };
+    use crate::simd::{Order, SIMDField};
+
+    fn check_mmap<T: SIMDField + std::fmt::Debug>(nums: &[T], probes: &[T]) {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        write_sorted_array(&mut file, nums).unwrap();
+        file.flush().unwrap();
+        let array = MmapSortedArray::<T>::open(file.path()).unwrap();
+        assert_eq!(array.len(), nums.len());
+        assert_eq!(array.as_slice(), nums);
+        assert_eq!(array.order(), Order::Ascending);
+        for p in probes {
+            match array.search(*p) {
+                Some(i) => assert_eq!(nums[i], *p),
+                None => assert!(nums.binary_search(p).is_err()),
+            }
+        }
+
+        let rev = nums.iter().rev().copied().collect::<Vec<_>>();
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        write_sorted_array_ordered(&mut file, &rev, Order::Descending).unwrap();
+        file.flush().unwrap();
+        let array = MmapSortedArray::<T>::open(file.path()).unwrap();
+        assert_eq!(array.order(), Order::Descending);
+        assert_eq!(array.as_slice(), rev);
+        for p in probes {
+            match array.search(*p) {
+                Some(i) => assert_eq!(rev[i], *p),
+                None => assert!(nums.binary_search(p).is_err()),
+            }
+        }
+    }
+
+    #[test]
+    fn test_mmap_roundtrip() {
+        let nums = (0..100_000u32).map(|i| i * 3).collect::<Vec<_>>();
+        check_mmap(&nums, &(0..300_010).step_by(7).collect::<Vec<_>>());
+        let nums = (-500..500i16).map(|i| i * 2).collect::<Vec<_>>();
+        check_mmap(&nums, &(i16::MIN..=i16::MAX).step_by(3).collect::<Vec<_>>());
+        let nums = (0..70_000i64)
+            .map(|i| i * i - (1 << 40))
+            .collect::<Vec<_>>();
+        check_mmap(&nums, &nums.iter().map(|n| n + 1).collect::<Vec<_>>());
+        check_mmap::<u8>(&[], &[0, 1, u8::MAX]);
+        check_mmap(&[7u64], &[0, 7, 8]);
+    }
+
+    #[test]
+    fn test_mmap_validation() {
+        let nums = (0..1000u32).collect::<Vec<_>>();
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        write_sorted_array(&mut file, &nums).unwrap();
+        file.flush().unwrap();
+        assert_eq!(read_type_tag(file.path()).unwrap(), type_tag::<u32>());
+        assert!(matches!(
+            MmapSortedArray::<i32>::open(file.path()),
+            Err(MmapError::TypeTag {
+                expected: 0x84,
+                found: 4
+            })
+        ));
+        assert!(matches!(
+            MmapSortedArray::<u64>::open(file.path()),
+            Err(MmapError::TypeTag { .. })
+        ));
+        // as if written on a host of the other byte order
+        let foreign = if cfg!(target_endian = "big") { 0 } else { 1 };
+        file.seek(SeekFrom::Start(13)).unwrap();
+        file.write_all(&[foreign]).unwrap();
+        file.flush().unwrap();
+        assert!(matches!(
+            MmapSortedArray::<u32>::open(file.path()),
+            Err(MmapError::ByteOrder)
+        ));
+        file.seek(SeekFrom::Start(13)).unwrap();
+        file.write_all(&[1 - foreign]).unwrap();
+        file.flush().unwrap();
+        file.seek(SeekFrom::Start(14)).unwrap();
+        file.write_all(&[2]).unwrap();
+        file.flush().unwrap();
+        assert!(matches!(
+            MmapSortedArray::<u32>::open(file.path()),
+            Err(MmapError::Order(2))
+        ));
+        file.seek(SeekFrom::Start(14)).unwrap();
+        file.write_all(&[0]).unwrap();
+        file.flush().unwrap();
+        assert_eq!(
+            MmapSortedArray::<u32>::open(file.path()).unwrap().len(),
+            1000
+        );
+        // flip one payload bit
+        file.seek(SeekFrom::Start(HEADER_LEN as u64 + 10)).unwrap();
+        file.write_all(&[0xFF]).unwrap();
+        file.flush().unwrap();
+        assert!(matches!(
+            MmapSortedArray::<u32>::open(file.path()),
+            Err(MmapError::Checksum)
+        ));
+        // drop the last element
+        file.as_file().set_len(HEADER_LEN as u64 + 999 * 4).unwrap();
+        assert!(matches!(
+            MmapSortedArray::<u32>::open(file.path()),
+            Err(MmapError::Length)
+        ));
+        file.seek(SeekFrom::Start(0)).unwrap();
+        file.write_all(b"NOTSORT\0").unwrap();
+        file.flush().unwrap();
+        assert!(matches!(
+            MmapSortedArray::<u32>::open(file.path()),
+            Err(MmapError::Magic)
+        ));
+        let empty = tempfile::NamedTempFile::new().unwrap();
+        assert!(matches!(
+            MmapSortedArray::<u32>::open(empty.path()),
+            Err(MmapError::Magic)
+        ));
+        assert!(matches!(read_type_tag(empty.path()), Err(MmapError::Magic)));
+
+        let mut sink = Vec::new();
+        assert!(write_sorted_array(&mut sink, &[2u8, 1]).is_err());
+        assert!(write_sorted_array_ordered(&mut sink, &[1u8, 2], Order::Descending).is_err());
+        assert!(sink.is_empty());
+    }
+}
@@ -0,0 +1,271 @@
+//! search over `ndarray` views
+//!
+//! contiguous views borrow their slice and go straight to the SIMD kernels, strided views fall
+//! back to a scalar bisection or gather their elements into a buffer for the SIMD scan
+
+use ndarray::{Array1, ArrayView1, ArrayView2, Axis};
+
+use crate::binary_search_ordered;
+use crate::simd::{self, Order, SIMDField, Side};
+
+/// elements gathered per SIMD scan of a strided view
+const GATHER: usize = 256;
+
+/// index of an element equal to `target` in the sorted `view`
+pub fn search<T: SIMDField>(view: ArrayView1<'_, T>, target: T) -> Option<usize> {
+    search_ordered(view, target, Order::Ascending)
+}
+
+/// `search` over `view` sorted in `order`
+pub fn search_ordered<T: SIMDField>(
+    view: ArrayView1<'_, T>,
+    target: T,
+    order: Order,
+) -> Option<usize> {
+    match view.as_slice() {
+        Some(nums) => binary_search_ordered(nums, target, order),
+        None => {
+            let i = lower_bound(&view, target, Side::Left, order);
+            (i < view.len() && view[i] == target).then_some(i)
+        }
+    }
+}
+
+/// index of the first element equal to `target`, `view` doesn't need to be sorted
+pub fn linear_search<T: SIMDField>(view: ArrayView1<'_, T>, target: T) -> Option<usize> {
+    if let Some(nums) = view.as_slice() {
+        return simd::linear_search(nums, target);
+    }
+    let mut buf = [T::zero(); GATHER];
+    let mut from = 0;
+    // one pass over the view, each chunk picks up where the last one stopped
+    let mut iter = view.iter();
+    while from < view.len() {
+        let n = (view.len() - from).min(GATHER);
+        for (b, x) in buf[..n].iter_mut().zip(iter.by_ref()) {
+            *b = *x;
+        }
+        if let Some(i) = simd::linear_search(&buf[..n], target) {
+            return Some(from + i);
+        }
+        from += n;
+    }
+    None
+}
+
+/// `searchsorted` with the sorted `view` as edges
+///
+/// panics if `out` and `values` differ in length
+pub fn searchsorted<T: SIMDField>(
+    view: ArrayView1<'_, T>,
+    values: &[T],
+    side: Side,
+    out: &mut [usize],
+) {
+    searchsorted_ordered(view, values, side, Order::Ascending, out)
+}
+
+/// `searchsorted` over `view` sorted in `order`
+///
+/// panics if `out` and `values` differ in length
+pub fn searchsorted_ordered<T: SIMDField>(
+    view: ArrayView1<'_, T>,
+    values: &[T],
+    side: Side,
+    order: Order,
+    out: &mut [usize],
+) {
+    match view.as_slice() {
+        Some(edges) => simd::searchsorted_ordered(edges, values, side, order, out),
+        None => {
+            assert_eq!(
+                values.len(),
+                out.len(),
+                "searchsorted: `out` must be as long as `values`"
+            );
+            for (value, rank) in values.iter().zip(out.iter_mut()) {
+                *rank = lower_bound(&view, *value, side, order);
+            }
+        }
+    }
+}
+
+/// `search` on every lane of `view` along `axis`, lane `i` looks for `targets[i]`
+///
+/// rows of a standard layout array are contiguous lanes for `Axis(1)`, columns for `Axis(0)`
+/// are strided and take the scalar path
+///
+/// panics if `targets` doesn't hold one target per lane
+pub fn search_along_axis<T: SIMDField>(
+    view: ArrayView2<'_, T>,
+    axis: Axis,
+    targets: &[T],
+) -> Array1<Option<usize>> {
+    search_along_axis_ordered(view, axis, targets, Order::Ascending)
+}
+
+/// `search_along_axis` over lanes sorted in `order`
+///
+/// panics if `targets` doesn't hold one target per lane
+pub fn search_along_axis_ordered<T: SIMDField>(
+    view: ArrayView2<'_, T>,
+    axis: Axis,
+    targets: &[T],
+    order: Order,
+) -> Array1<Option<usize>> {
+    let lanes = view.lanes(axis);
+    assert_eq!(
+        lanes.into_iter().len(),
+        targets.len(),
+        "search_along_axis: one target per lane"
+    );
+    view.lanes(axis)
+        .into_iter()
+        .zip(targets)
+        .map(|(lane, target)| search_ordered(lane, *target, order))
+        .collect()
+}
+
+// insertion point of `target` by scalar bisection, for views that aren't contiguous
+fn lower_bound<T: SIMDField>(
+    view: &ArrayView1<'_, T>,
+    target: T,
+    side: Side,
+    order: Order,
+) -> usize {
+    let (mut base, mut n) = (0, view.len());
+    while n > 0 {
+        let half = n / 2;
+        let pivot = view[base + half];
+        let right = match side {
+            Side::Left => order.before(pivot, target),
+            Side::Right => !order.before(target, pivot),
+        };
+        if right {
+            base += half + 1;
+            n -= half + 1;
+        } else {
+            n = half;
+        }
+    }
+    base
+}
+
+#[cfg(test)]
+mod tests {
+    use ndarray::{s, Array1, Array2, ArrayView1, Axis, ShapeBuilder};
+
+    use crate::nd::{
+        linear_search, search, search_along_axis, search_along_axis_ordered, search_ordered,
+        searchsorted, searchsorted_ordered,
+    };
+    use crate::simd::{Order, SIMDField, Side};
+
+    fn check_view<T: SIMDField + std::fmt::Debug>(view: ArrayView1<'_, T>, probes: &[T]) {
+        let nums = view.to_vec();
+        let mut out = vec![0; probes.len()];
+        searchsorted(view, probes, Side::Left, &mut out);
+        for (p, rank) in probes.iter().zip(&out) {
+            assert_eq!(*rank, nums.partition_point(|n| n < p));
+            match search(view, *p) {
+                Some(i) => assert_eq!(nums[i], *p),
+                None => assert!(!nums.contains(p)),
+            }
+            assert_eq!(linear_search(view, *p), nums.iter().position(|n| n == p));
+        }
+        searchsorted(view, probes, Side::Right, &mut out);
+        for (p, rank) in probes.iter().zip(&out) {
+            assert_eq!(*rank, nums.partition_point(|n| n <= p));
+        }
+
+        let rev = view.slice(s![..;-1]);
+        let desc = Order::Descending;
+        let nums = rev.to_vec();
+        searchsorted_ordered(rev, probes, Side::Left, desc, &mut out);
+        for (p, rank) in probes.iter().zip(&out) {
+            assert_eq!(*rank, nums.partition_point(|n| n > p));
+            match search_ordered(rev, *p, desc) {
+                Some(i) => assert_eq!(nums[i], *p),
+                None => assert!(!nums.contains(p)),
+            }
+        }
+        searchsorted_ordered(rev, probes, Side::Right, desc, &mut out);
+        for (p, rank) in probes.iter().zip(&out) {
+            assert_eq!(*rank, nums.partition_point(|n| n >= p));
+        }
+    }
+
+    #[test]
+    fn test_u32s_views() {
+        let array = Array1::from_iter((0..3000u32).map(|i| i / 2 * 5));
+        let probes = (0..7600u32).step_by(3).collect::<Vec<_>>();
+        check_view(array.view(), &probes);
+        check_view(array.slice(s![..;2]), &probes);
+        check_view(array.slice(s![7..;3]), &probes);
+        check_view(array.slice(s![..0]), &probes);
+    }
+
+    #[test]
+    fn test_i16s_views() {
+        let array = Array1::from_iter((0..1000i16).map(|i| i * 3 - 1500));
+        let probes = (-1600..1600i16).collect::<Vec<_>>();
+        check_view(array.view(), &probes);
+        check_view(array.slice(s![1..;5]), &probes);
+    }
+
+    #[test]
+    fn test_i64s_search_along_axis() {
+        // rows and columns are both sorted
+        for layout in [false, true] {
+            let array = Array2::from_shape_fn((60, 200).set_f(layout), |(i, j)| {
+                i as i64 * 1000 + j as i64 * 3 - 5000
+            });
+            // row `i` looks for its column `4 * i`, which is past the end from row 50 on
+            let row_targets = (0..60)
+                .map(|i| i * 1000 + i * 12 - 5000)
+                .collect::<Vec<_>>();
+            let rows = search_along_axis(array.view(), Axis(1), &row_targets);
+            for (i, hit) in rows.iter().enumerate() {
+                let expected = (i < 50).then_some(i * 4);
+                assert_eq!(*hit, expected);
+            }
+            let col_targets = (0..200)
+                .map(|j| 7000 + j * 3 - 5000 + 1)
+                .collect::<Vec<_>>();
+            let cols = search_along_axis(array.view(), Axis(0), &col_targets);
+            assert!(cols.iter().all(|hit| hit.is_none()));
+            let col_targets = (0..200).map(|j| 7000 + j * 3 - 5000).collect::<Vec<_>>();
+            let cols = search_along_axis(array.view(), Axis(0), &col_targets);
+            assert!(cols.iter().all(|hit| *hit == Some(7)));
+        }
+    }
+
+    #[test]
+    fn test_descending_search_along_axis() {
+        // newest-first rows, reversing a standard layout array makes them strided
+        let array = Array2::from_shape_fn((40, 100), |(i, j)| i as u32 * 1000 + j as u32 * 3);
+        let desc = array.slice(s![..;-1, ..;-1]);
+        let row_targets = (0..40u32)
+            .map(|i| (39 - i) * 1000 + (i % 5) * 3 + i % 2)
+            .collect::<Vec<_>>();
+        let rows = search_along_axis_ordered(desc, Axis(1), &row_targets, Order::Descending);
+        for (i, hit) in rows.iter().enumerate() {
+            let expected = (i % 2 == 0).then_some(99 - i % 5);
+            assert_eq!(*hit, expected);
+        }
+        // contiguous descending rows take the SIMD path
+        let array =
+            Array2::from_shape_fn((40, 100), |(i, j)| i as u32 * 1000 + (99 - j as u32) * 3);
+        let row_targets = (0..40u32).map(|i| i * 1000 + 297).collect::<Vec<_>>();
+        let rows =
+            search_along_axis_ordered(array.view(), Axis(1), &row_targets, Order::Descending);
+        assert!(rows.iter().all(|hit| *hit == Some(0)));
+    }
+
+    #[test]
+    #[should_panic(expected = "one target per lane")]
+    fn test_search_along_axis_targets() {
+        let array = Array2::<u8>::zeros((3, 4));
+        search_along_axis(array.view(), Axis(0), &[0, 0, 0]);
+    }
+}
@@ -0,0 +1,312 @@
+use crate::simd::{linear_search, searchsorted, SIMDField, Side};
+
+/// elements per frame-of-reference block
+pub const BLOCK_LEN: usize = 128;
+
+/// sorted integers compressed as frame-of-reference + bit packing in blocks of `BLOCK_LEN`
+///
+/// every block stores its first element as base and the deltas to it with just enough bits
+/// for the largest one, a skip table of block maxima routes a query to the one block to decode
+#[derive(Debug, Clone)]
+pub struct PackedSortedArray<T> {
+    len: usize,
+    // last element of every block
+    maxima: Vec<T>,
+    // first element of every block
+    bases: Vec<T>,
+    // bits per delta of every block
+    widths: Vec<u8>,
+    // first word of every block in `words`
+    offsets: Vec<usize>,
+    words: Vec<u64>,
+}
+
+impl<T: SIMDField> PackedSortedArray<T> {
+    /// panics if `nums` is not sorted ascending
+    pub fn new(nums: &[T]) -> Self {
+        assert!(
+            nums.windows(2).all(|w| w[0] <= w[1]),
+            "PackedSortedArray: input must be sorted"
+        );
+        let blocks = nums.len().div_ceil(BLOCK_LEN);
+        let mut packed = PackedSortedArray {
+            len: nums.len(),
+            maxima: Vec::with_capacity(blocks),
+            bases: Vec::with_capacity(blocks),
+            widths: Vec::with_capacity(blocks),
+            offsets: Vec::with_capacity(blocks),
+            words: Vec::new(),
+        };
+        for block in nums.chunks(BLOCK_LEN) {
+            let base = block[0];
+            let max = block[block.len() - 1];
+            let width = 64 - delta(base, max).leading_zeros();
+            packed.maxima.push(max);
+            packed.bases.push(base);
+            packed.widths.push(width as u8);
+            packed.offsets.push(packed.words.len());
+            pack(
+                block.iter().map(|n| delta(base, *n)),
+                width,
+                &mut packed.words,
+            );
+        }
+        packed
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// compressed footprint in bytes, excluding the struct itself
+    pub fn size_in_bytes(&self) -> usize {
+        let t = T::size_in_bits() / 8;
+        self.maxima.len() * (2 * t + 1 + std::mem::size_of::<usize>()) + self.words.len() * 8
+    }
+
+    pub fn get(&self, i: usize) -> Option<T> {
+        if i >= self.len {
+            return None;
+        }
+        let (block, lane) = (i / BLOCK_LEN, i % BLOCK_LEN);
+        let width = self.widths[block] as u32;
+        let d = unpack(&self.words[self.offsets[block]..], width, lane);
+        Some(undelta(self.bases[block], d))
+    }
+
+    pub fn contains(&self, target: T) -> bool {
+        let block = match self.block_of(target) {
+            Some(block) => block,
+            None => return false,
+        };
+        let mut buf = [T::zero(); BLOCK_LEN];
+        let nums = self.decode_block(block, &mut buf);
+        linear_search(nums, target).is_some()
+    }
+
+    /// number of elements less than `target`
+    pub fn rank(&self, target: T) -> usize {
+        let block = match self.block_of(target) {
+            Some(block) => block,
+            None => return self.len,
+        };
+        let mut buf = [T::zero(); BLOCK_LEN];
+        let nums = self.decode_block(block, &mut buf);
+        let mut lane = [0];
+        searchsorted(nums, &[target], Side::Left, &mut lane);
+        block * BLOCK_LEN + lane[0]
+    }
+
+    /// index and value of the first element not less than `target`
+    pub fn lower_bound(&self, target: T) -> Option<(usize, T)> {
+        let block = self.block_of(target)?;
+        let mut buf = [T::zero(); BLOCK_LEN];
+        let nums = self.decode_block(block, &mut buf);
+        let mut lane = [0];
+        searchsorted(nums, &[target], Side::Left, &mut lane);
+        // the block max is >= target, so the lane is always inside the block
+        Some((block * BLOCK_LEN + lane[0], nums[lane[0]]))
+    }
+
+    pub fn to_vec(&self) -> Vec<T> {
+        let mut out = Vec::with_capacity(self.len);
+        let mut buf = [T::zero(); BLOCK_LEN];
+        for block in 0..self.maxima.len() {
+            out.extend_from_slice(self.decode_block(block, &mut buf));
+        }
+        out
+    }
+
+    // first block whose max is >= target
+    fn block_of(&self, target: T) -> Option<usize> {
+        let mut block = [0];
+        searchsorted(&self.maxima, &[target], Side::Left, &mut block);
+        if block[0] < self.maxima.len() {
+            Some(block[0])
+        } else {
+            None
+        }
+    }
+
+    fn decode_block<'a>(&self, block: usize, buf: &'a mut [T; BLOCK_LEN]) -> &'a [T] {
+        let len = (self.len - block * BLOCK_LEN).min(BLOCK_LEN);
+        let base = self.bases[block];
+        let width = self.widths[block] as u32;
+        let words = &self.words[self.offsets[block]..];
+        for (lane, n) in buf[..len].iter_mut().enumerate() {
+            *n = undelta(base, unpack(words, width, lane));
+        }
+        &buf[..len]
+    }
+}
+
+// `n - base` as unsigned, wrapping arithmetic keeps it correct for signed `T` too
+#[inline(always)]
+fn delta<T: SIMDField>(base: T, n: T) -> u64 {
+    let d = n.unchecked_u64().wrapping_sub(base.unchecked_u64());
+    match T::size_in_bits() {
+        64 => d,
+        bits => d & ((1 << bits) - 1),
+    }
+}
+
+#[inline(always)]
+fn undelta<T: SIMDField>(base: T, d: u64) -> T {
+    T::unchecked_from_u64(base.unchecked_u64().wrapping_add(d))
+}
+
+pub(crate) fn pack(deltas: impl Iterator<Item = u64>, width: u32, words: &mut Vec<u64>) {
+    if width == 0 {
+        return;
+    }
+    let start = words.len();
+    let width = width as usize;
+    for (i, d) in deltas.enumerate() {
+        let (word, shift) = (start + i * width / 64, i * width % 64);
+        if word >= words.len() {
+            words.push(0);
+        }
+        words[word] |= d << shift;
+        if shift + width > 64 {
+            words.push(d >> (64 - shift));
+        }
+    }
+}
+
+#[inline(always)]
+pub(crate) fn unpack(words: &[u64], width: u32, i: usize) -> u64 {
+    if width == 0 {
+        return 0;
+    }
+    let width = width as usize;
+    let (word, shift) = (i * width / 64, i * width % 64);
+    let mut d = words[word] >> shift;
+    if shift + width > 64 {
+        d |= words[word + 1] << (64 - shift);
+    }
+    if width == 64 {
+        d
+    } else {
+        d & ((1 << width) - 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::packed::PackedSortedArray;
+    use crate::simd::SIMDField;
+
+    fn check_packed<T: SIMDField + std::fmt::Debug>(nums: &[T], probes: &[T]) {
+        let packed = PackedSortedArray::new(nums);
+        assert_eq!(packed.len(), nums.len());
+        assert_eq!(packed.to_vec(), nums);
+        for (i, n) in nums.iter().enumerate() {
+            assert_eq!(packed.get(i), Some(*n));
+        }
+        assert_eq!(packed.get(nums.len()), None);
+        for p in probes {
+            let rank = nums.partition_point(|n| n < p);
+            assert_eq!(packed.contains(*p), nums.contains(p));
+            assert_eq!(packed.rank(*p), rank);
+            assert_eq!(packed.lower_bound(*p), nums.get(rank).map(|n| (rank, *n)));
+        }
+    }
+
+    #[test]
+    fn test_u8s_packed() {
+        let probes = (0..=u8::MAX).collect::<Vec<_>>();
+        for size in [0, 1, 127, 128, 129, 300, 1000] {
+            let nums = (0..size)
+                .map(|i| (i * 255 / 1000) as u8)
+                .collect::<Vec<_>>();
+            check_packed(&nums, &probes);
+        }
+    }
+
+    #[test]
+    fn test_i8s_packed() {
+        let probes = (i8::MIN..=i8::MAX).collect::<Vec<_>>();
+        for size in [0, 1, 127, 128, 129, 300, 1000] {
+            let nums = (0..size)
+                .map(|i| (i * 255 / 1000 - 128) as i8)
+                .collect::<Vec<_>>();
+            check_packed(&nums, &probes);
+        }
+    }
+
+    #[test]
+    fn test_u16s_packed() {
+        let probes = (0..=u16::MAX).step_by(7).collect::<Vec<_>>();
+        for size in [0, 1, 128, 129, 1000, 5000] {
+            let nums = (0..size as u64)
+                .map(|i| (i * i * 65535 / 25_000_000) as u16)
+                .collect::<Vec<_>>();
+            check_packed(&nums, &probes);
+        }
+    }
+
+    #[test]
+    fn test_i16s_packed() {
+        let probes = (i16::MIN..=i16::MAX).step_by(7).collect::<Vec<_>>();
+        for size in [0, 1, 128, 129, 1000, 5000] {
+            let nums = (0..size as i64)
+                .map(|i| (i * i * 65535 / 25_000_000 - 32768) as i16)
+                .collect::<Vec<_>>();
+            check_packed(&nums, &probes);
+        }
+    }
+
+    #[test]
+    fn test_u32s_packed() {
+        let nums = (0..3000u64)
+            .map(|i| (i * i * 477) as u32)
+            .collect::<Vec<_>>();
+        let probes = nums
+            .iter()
+            .flat_map(|n| [n.wrapping_sub(1), *n, n.wrapping_add(1)])
+            .collect::<Vec<_>>();
+        check_packed(&nums, &probes);
+        check_packed(&[0, u32::MAX], &[0, 1, u32::MAX - 1, u32::MAX]);
+    }
+
+    #[test]
+    fn test_i32s_packed() {
+        let nums = (0..3000i64)
+            .map(|i| (i * i * 477 - (1 << 31)) as i32)
+            .collect::<Vec<_>>();
+        let probes = nums
+            .iter()
+            .flat_map(|n| [n.wrapping_sub(1), *n, n.wrapping_add(1)])
+            .collect::<Vec<_>>();
+        check_packed(&nums, &probes);
+        check_packed(&[i32::MIN, i32::MAX], &[i32::MIN, 0, i32::MAX]);
+    }
+
+    #[test]
+    fn test_u64s_packed() {
+        let nums = (0..3000u64).map(|i| i.pow(5) * 75).collect::<Vec<_>>();
+        let probes = nums
+            .iter()
+            .flat_map(|n| [n.wrapping_sub(1), *n, n.wrapping_add(1)])
+            .collect::<Vec<_>>();
+        check_packed(&nums, &probes);
+        check_packed(&[0, 5, 5, 5, u64::MAX], &[0, 4, 5, 6, u64::MAX]);
+    }
+
+    #[test]
+    fn test_i64s_packed() {
+        let nums = (0..3000i128)
+            .map(|i| (i.pow(5) * 75 - (1 << 63)) as i64)
+            .collect::<Vec<_>>();
+        let probes = nums
+            .iter()
+            .flat_map(|n| [n.wrapping_sub(1), *n, n.wrapping_add(1)])
+            .collect::<Vec<_>>();
+        check_packed(&nums, &probes);
+        check_packed(&[i64::MIN, 0, i64::MAX], &[i64::MIN, -1, 0, 1, i64::MAX]);
+    }
+}
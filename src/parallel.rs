@@ -0,0 +1,116 @@
+use rayon::prelude::*;
+
+use crate::simd::{linear_search, search_batch, searchsorted, SIMDField, Side};
+
+/// elements scanned per task by `par_linear_search`
+const LINEAR_CHUNK: usize = 1 << 16;
+
+/// fewest targets per task of `par_search_batch`
+const MIN_TARGETS: usize = 1 << 10;
+
+/// `out[j]` is the index of the first element equal to `targets[j]` in the sorted `nums`
+///
+/// targets are split across threads, when they are sorted too every thread only searches the
+/// region of `nums` between its first and last target
+pub fn par_search_batch<T: SIMDField + Sync>(nums: &[T], targets: &[T]) -> Vec<Option<usize>> {
+    let mut out = vec![None; targets.len()];
+    let tasks = rayon::current_num_threads() * 4;
+    let chunk = targets.len().div_ceil(tasks).max(MIN_TARGETS);
+    let sorted = targets.windows(2).all(|w| w[0] <= w[1]);
+    targets
+        .par_chunks(chunk)
+        .zip(out.par_chunks_mut(chunk))
+        .for_each(|(targets, out)| {
+            let (from, to) = if sorted {
+                // disjoint region of `nums` holding every target of this chunk
+                let mut bounds = [0; 2];
+                searchsorted(nums, &targets[..1], Side::Left, &mut bounds[..1]);
+                searchsorted(
+                    nums,
+                    &targets[targets.len() - 1..],
+                    Side::Right,
+                    &mut bounds[1..],
+                );
+                (bounds[0], bounds[1])
+            } else {
+                (0, nums.len())
+            };
+            search_batch(&nums[from..to], from, targets, out);
+        });
+    out
+}
+
+/// index of the first element equal to `target`, the unsorted `nums` is scanned in parallel
+pub fn par_linear_search<T: SIMDField + Sync>(nums: &[T], target: T) -> Option<usize> {
+    nums.par_chunks(LINEAR_CHUNK)
+        .enumerate()
+        .find_map_first(|(i, chunk)| linear_search(chunk, target).map(|j| i * LINEAR_CHUNK + j))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parallel::{par_linear_search, par_search_batch};
+    use crate::simd::SIMDField;
+
+    fn check_par_search<T: SIMDField + Sync + std::fmt::Debug>(nums: &[T], targets: &[T]) {
+        let expected = targets
+            .iter()
+            .map(|t| {
+                let i = nums.partition_point(|n| n < t);
+                (nums.get(i) == Some(t)).then_some(i)
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(par_search_batch(nums, targets), expected);
+        let mut sorted = targets.to_vec();
+        sorted.sort();
+        let expected = sorted
+            .iter()
+            .map(|t| {
+                let i = nums.partition_point(|n| n < t);
+                (nums.get(i) == Some(t)).then_some(i)
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(par_search_batch(nums, &sorted), expected);
+    }
+
+    #[test]
+    fn test_u32s_par_search_batch() {
+        let nums = (0..1_000_000u32).map(|i| i / 2 * 3).collect::<Vec<_>>();
+        let targets = (0..100_000u32)
+            .map(|i| i.wrapping_mul(2654435761) % 1_600_000)
+            .collect::<Vec<_>>();
+        check_par_search(&nums, &targets);
+        check_par_search(&nums, &[]);
+        check_par_search(&[], &targets[..10]);
+    }
+
+    #[test]
+    fn test_i64s_par_search_batch() {
+        let nums = (0..300_000i64)
+            .map(|i| i * 7 - 1_000_000)
+            .collect::<Vec<_>>();
+        let targets = (0..50_000i64)
+            .map(|i| (i * 7919) % 2_200_000 - 1_100_000)
+            .collect::<Vec<_>>();
+        check_par_search(&nums, &targets);
+    }
+
+    #[test]
+    fn test_par_linear_search() {
+        let nums = (0..500_000u32)
+            .map(|i| i.wrapping_mul(2654435761) >> 8)
+            .collect::<Vec<_>>();
+        for target in nums.iter().step_by(9973).chain([&u32::MAX]) {
+            assert_eq!(
+                par_linear_search(&nums, *target),
+                nums.iter().position(|n| n == target)
+            );
+        }
+        let mut dups = vec![5u16; 300_000];
+        dups[200_000] = 7;
+        dups[250_000] = 7;
+        assert_eq!(par_linear_search(&dups, 7), Some(200_000));
+        assert_eq!(par_linear_search(&dups, 5), Some(0));
+        assert_eq!(par_linear_search(&[] as &[u16], 5), None);
+    }
+}
@@ -0,0 +1,274 @@
+use std::fmt;
+use std::ops::RangeInclusive;
+
+use crate::simd::{searchsorted, SIMDField, Side};
+
+/// keys resolved per `searchsorted` call by `get_batch`
+const BATCH_KEYS: usize = 256;
+
+/// why a set of intervals can't form a `RangeMap`, indices refer to the input order
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RangeMapError {
+    /// interval `index` ends before it starts
+    Inverted { index: usize },
+    /// intervals `first` and `second` share at least one key
+    Overlap { first: usize, second: usize },
+}
+
+impl fmt::Display for RangeMapError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RangeMapError::Inverted { index } => {
+                write!(f, "RangeMap: interval {} ends before it starts", index)
+            }
+            RangeMapError::Overlap { first, second } => {
+                write!(f, "RangeMap: intervals {} and {} overlap", first, second)
+            }
+        }
+    }
+}
+
+impl std::error::Error for RangeMapError {}
+
+/// disjoint inclusive intervals of `K` mapped to values
+///
+/// interval starts are stored contiguously, a lookup is a SIMD predecessor search over them
+/// followed by a check against the end of the interval found, keys in gaps map to nothing
+#[derive(Debug, Clone)]
+pub struct RangeMap<K, V> {
+    starts: Vec<K>,
+    ends: Vec<K>,
+    values: Vec<V>,
+}
+
+impl<K: SIMDField, V> RangeMap<K, V> {
+    /// intervals may come in any order but must not be empty nor overlap
+    pub fn new<I>(intervals: I) -> Result<Self, RangeMapError>
+    where
+        I: IntoIterator<Item = (RangeInclusive<K>, V)>,
+    {
+        let mut intervals = intervals
+            .into_iter()
+            .enumerate()
+            .map(|(index, (range, value))| {
+                let (start, end) = range.into_inner();
+                if end < start {
+                    Err(RangeMapError::Inverted { index })
+                } else {
+                    Ok((index, start, end, value))
+                }
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        intervals.sort_by_key(|(_, start, _, _)| *start);
+        for w in intervals.windows(2) {
+            let ((first, _, end, _), (second, start, _, _)) = (&w[0], &w[1]);
+            if *start <= *end {
+                return Err(RangeMapError::Overlap {
+                    first: *first.min(second),
+                    second: *first.max(second),
+                });
+            }
+        }
+        let mut map = RangeMap {
+            starts: Vec::with_capacity(intervals.len()),
+            ends: Vec::with_capacity(intervals.len()),
+            values: Vec::with_capacity(intervals.len()),
+        };
+        for (_, start, end, value) in intervals {
+            map.starts.push(start);
+            map.ends.push(end);
+            map.values.push(value);
+        }
+        Ok(map)
+    }
+
+    /// number of intervals
+    pub fn len(&self) -> usize {
+        self.starts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.starts.is_empty()
+    }
+
+    pub fn get(&self, key: K) -> Option<&V> {
+        self.find(key).map(|i| &self.values[i])
+    }
+
+    /// the interval holding `key` and its value
+    pub fn get_key_value(&self, key: K) -> Option<(RangeInclusive<K>, &V)> {
+        self.find(key)
+            .map(|i| (self.starts[i]..=self.ends[i], &self.values[i]))
+    }
+
+    pub fn contains_key(&self, key: K) -> bool {
+        self.find(key).is_some()
+    }
+
+    /// `out[j]` is the value of the interval holding `keys[j]`
+    ///
+    /// panics if `out` and `keys` differ in length
+    pub fn get_batch<'a>(&'a self, keys: &[K], out: &mut [Option<&'a V>]) {
+        assert_eq!(
+            keys.len(),
+            out.len(),
+            "RangeMap: `out` must be as long as `keys`"
+        );
+        let mut ranks = [0usize; BATCH_KEYS];
+        for (keys, out) in keys.chunks(BATCH_KEYS).zip(out.chunks_mut(BATCH_KEYS)) {
+            let ranks = &mut ranks[..keys.len()];
+            searchsorted(&self.starts, keys, Side::Right, ranks);
+            for ((rank, key), value) in ranks.iter().zip(keys).zip(out.iter_mut()) {
+                *value = self.covering(*rank, *key).map(|i| &self.values[i]);
+            }
+        }
+    }
+
+    /// intervals and values in ascending order, `rev()` walks them in descending order
+    pub fn iter(
+        &self,
+    ) -> impl DoubleEndedIterator<Item = (RangeInclusive<K>, &V)> + ExactSizeIterator + '_ {
+        self.starts
+            .iter()
+            .zip(&self.ends)
+            .zip(&self.values)
+            .map(|((start, end), value)| (*start..=*end, value))
+    }
+
+    // index of the interval holding `key`
+    #[inline(always)]
+    fn find(&self, key: K) -> Option<usize> {
+        let mut rank = [0];
+        searchsorted(&self.starts, &[key], Side::Right, &mut rank);
+        self.covering(rank[0], key)
+    }
+
+    // `rank` intervals start at or before `key`, the last of them holds it unless `key` is in a gap
+    #[inline(always)]
+    fn covering(&self, rank: usize, key: K) -> Option<usize> {
+        let i = rank.checked_sub(1)?;
+        if key <= self.ends[i] {
+            Some(i)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ops::RangeInclusive;
+
+    use crate::range_map::{RangeMap, RangeMapError};
+    use crate::simd::SIMDField;
+
+    fn check_range_map<T: SIMDField + std::fmt::Debug>(
+        intervals: &[RangeInclusive<T>],
+        keys: &[T],
+    ) {
+        let map = RangeMap::new(intervals.iter().cloned().zip(0..)).unwrap();
+        assert_eq!(map.len(), intervals.len());
+        let naive = |key: &T| intervals.iter().position(|r| r.contains(key));
+        for key in keys {
+            let i = naive(key);
+            assert_eq!(map.get(*key), i.as_ref());
+            assert_eq!(map.contains_key(*key), i.is_some());
+            assert_eq!(
+                map.get_key_value(*key).map(|(r, v)| (r, *v)),
+                i.map(|i| (intervals[i].clone(), i))
+            );
+        }
+        let mut out = vec![None; keys.len()];
+        map.get_batch(keys, &mut out);
+        let expected = keys.iter().map(naive).collect::<Vec<_>>();
+        assert_eq!(out, expected.iter().map(|i| i.as_ref()).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_u8s_range_map() {
+        let keys = (0..=u8::MAX).collect::<Vec<_>>();
+        for size in 0..50u8 {
+            let intervals = (0..size).map(|i| i * 5..=i * 5 + i % 4).collect::<Vec<_>>();
+            check_range_map(&intervals, &keys);
+        }
+        check_range_map(&[0..=u8::MAX], &keys);
+    }
+
+    #[test]
+    fn test_i16s_range_map() {
+        let keys = (-2000..2000i16).collect::<Vec<_>>();
+        for size in [0, 1, 2, 17, 100, 300] {
+            let intervals = (0..size)
+                .map(|i| i * 11 - 1500..=i * 11 - 1500 + i % 7)
+                .collect::<Vec<_>>();
+            check_range_map(&intervals, &keys);
+        }
+    }
+
+    #[test]
+    fn test_u32s_range_map() {
+        let intervals = (0..1000u32)
+            .map(|i| i << 20..=(i << 20) + (i % 3) * 1000)
+            .collect::<Vec<_>>();
+        let keys = (0..3000u32)
+            .flat_map(|i| [i << 19, (i << 19) + 1000, (i << 19) + 2001])
+            .chain([u32::MAX])
+            .collect::<Vec<_>>();
+        check_range_map(&intervals, &keys);
+    }
+
+    #[test]
+    fn test_i64s_range_map() {
+        let intervals = [i64::MIN..=-5, -3..=-3, 0..=10, 11..=i64::MAX];
+        check_range_map(
+            &intervals,
+            &[i64::MIN, -6, -5, -4, -3, -2, -1, 0, 10, 11, i64::MAX],
+        );
+    }
+
+    #[test]
+    fn test_range_map_unsorted() {
+        let map = RangeMap::new([(20..=29u32, 'c'), (0..=9, 'a'), (10..=15, 'b')]).unwrap();
+        assert_eq!(map.get(16), None);
+        assert_eq!(map.get(15), Some(&'b'));
+        assert_eq!(map.get_key_value(25), Some((20..=29, &'c')));
+        assert_eq!(
+            map.iter().map(|(r, v)| (r, *v)).collect::<Vec<_>>(),
+            [(0..=9, 'a'), (10..=15, 'b'), (20..=29, 'c')]
+        );
+    }
+
+    #[test]
+    fn test_range_map_descending() {
+        // score bands from the top of a leaderboard down
+        let bands = (0..100i32)
+            .rev()
+            .map(|i| i * 100..=i * 100 + 49)
+            .collect::<Vec<_>>();
+        check_range_map(&bands, &(-10..10_010).collect::<Vec<_>>());
+        let map = RangeMap::new(bands.iter().cloned().zip(0..)).unwrap();
+        let desc = map.iter().rev().map(|(r, v)| (r, *v)).collect::<Vec<_>>();
+        assert_eq!(desc, bands.into_iter().zip(0..).collect::<Vec<_>>());
+        assert_eq!(map.iter().len(), 100);
+    }
+
+    #[test]
+    fn test_range_map_errors() {
+        #[allow(clippy::reversed_empty_ranges)]
+        let inverted = RangeMap::new([(0..=5u16, ()), (9..=7, ())]);
+        assert_eq!(inverted.unwrap_err(), RangeMapError::Inverted { index: 1 });
+        let overlap = RangeMap::new([(10..=20u16, ()), (0..=5, ()), (20..=30, ())]);
+        assert_eq!(
+            overlap.unwrap_err(),
+            RangeMapError::Overlap {
+                first: 0,
+                second: 2
+            }
+        );
+        let nested = RangeMap::new([(0..=100i32, ()), (40..=50, ())]);
+        assert_eq!(
+            nested.unwrap_err().to_string(),
+            "RangeMap: intervals 0 and 1 overlap"
+        );
+    }
+}
@@ -0,0 +1,147 @@
+use std::hint::select_unpredictable;
+use std::marker::PhantomData;
+use std::mem::size_of;
+
+use crate::binary_search_auto;
+use crate::simd::linear::{
+    cmpgt, lane_mask, load, load_tail, splat, sub, sum_bytes, to_signed, Vector,
+};
+use crate::simd::SIMDField;
+
+/// bytes ranked with one compare per vector and no branches, larger arrays are first bisected
+/// down to a window of this size
+pub const MAX_UNROLLED_BYTES: usize = 128;
+
+/// arrays past this size go through `binary_search_auto`
+const MAX_ARRAY_BYTES: usize = 1024;
+
+// layout of a `[T; N]` in vectors, known at compile time
+struct Fixed<T, const N: usize>(PhantomData<T>);
+
+impl<T, const N: usize> Fixed<T, N> {
+    const LANES: usize = size_of::<Vector>() / size_of::<T>();
+    // elements ranked by the unrolled compares
+    const WINDOW: usize = if N * size_of::<T>() <= MAX_UNROLLED_BYTES {
+        N
+    } else {
+        MAX_UNROLLED_BYTES / size_of::<T>()
+    };
+    const VECTORS: usize = Self::WINDOW / Self::LANES;
+    const UNROLLED: bool = N * size_of::<T>() <= MAX_ARRAY_BYTES;
+}
+
+/// index of the first element equal to `key` in the sorted `arr`, same as `binary_search_auto`
+///
+/// the layout is resolved at compile time: a branchless bisection down to `MAX_UNROLLED_BYTES`
+/// if `arr` is larger, then every vector of that window is compared against `key` at once
+pub fn search_array<T: SIMDField, const N: usize>(arr: &[T; N], key: T) -> Option<usize> {
+    if !Fixed::<T, N>::UNROLLED {
+        return binary_search_auto(arr, key);
+    }
+    let window = Fixed::<T, N>::WINDOW;
+    // the first element not less than `key` lies in [base, base + n]
+    let (mut base, mut n) = (0, N);
+    while n > window {
+        let half = n / 2;
+        base = select_unpredictable(arr[base + half] < key, base + half, base);
+        n -= half;
+    }
+    let from = base.min(N - window);
+    let rank = from + unsafe { rank_unrolled::<T, N>(&arr[from..from + window], key) };
+    (rank < N && arr[rank] == key).then_some(rank)
+}
+
+// number of elements of the `Fixed::WINDOW` long `nums` less than `key`
+//
+// every lane counts its hits across the vectors, all counts fit in a byte, so a single
+// horizontal byte sum at the end replaces a movemask + popcount per vector
+#[inline(always)]
+unsafe fn rank_unrolled<T: SIMDField, const N: usize>(nums: &[T], key: T) -> usize {
+    let lanes = Fixed::<T, N>::LANES;
+    let keys = to_signed::<T>(splat(key));
+    let mut counts = splat(T::zero());
+    for v in 0..Fixed::<T, N>::VECTORS {
+        let chunk = to_signed::<T>(load(nums.as_ptr().add(v * lanes)));
+        // a true lane is all ones, -1
+        counts = sub::<T>(counts, cmpgt::<T>(keys, chunk));
+    }
+    let mut rank = sum_bytes(counts) as usize;
+    let from = Fixed::<T, N>::VECTORS * lanes;
+    if from < nums.len() {
+        let (_, chunk, valid) = load_tail(nums, from);
+        let mask = lane_mask::<T>(cmpgt::<T>(keys, to_signed::<T>(chunk))) & valid;
+        rank += mask.count_ones() as usize;
+    }
+    rank
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::binary_search_auto;
+    use crate::simd::array::search_array;
+    use crate::simd::SIMDField;
+
+    fn check_search_array<T: SIMDField + std::fmt::Debug, const N: usize>(
+        f: impl Fn(usize) -> T,
+        probes: &[T],
+    ) {
+        let arr: [T; N] = std::array::from_fn(f);
+        for p in probes.iter().chain(&arr) {
+            let expected = arr.iter().position(|n| n == p);
+            assert_eq!(search_array(&arr, *p), expected, "{:?} in [_; {}]", p, N);
+            assert_eq!(binary_search_auto(&arr, *p), expected);
+        }
+    }
+
+    #[test]
+    fn test_u8s_search_array() {
+        let probes = (0..=u8::MAX).collect::<Vec<_>>();
+        check_search_array::<u8, 1>(|i| i as u8 + 3, &probes);
+        check_search_array::<u8, 15>(|i| i as u8 * 2, &probes);
+        check_search_array::<u8, 16>(|i| i as u8 * 2, &probes);
+        check_search_array::<u8, 33>(|i| i as u8 / 2 * 7, &probes);
+        check_search_array::<u8, 200>(|i| i as u8 + 55, &probes);
+        check_search_array::<u8, 255>(|i| i as u8, &probes);
+    }
+
+    #[test]
+    fn test_i16s_search_array() {
+        let probes = (-300..300i16).collect::<Vec<_>>();
+        check_search_array::<i16, 8>(|i| i as i16 * 40 - 150, &probes);
+        check_search_array::<i16, 17>(|i| i as i16 / 3 * 11 - 100, &probes);
+        check_search_array::<i16, 256>(|i| i as i16 * 2 - 256, &probes);
+        check_search_array::<i16, 257>(|i| i as i16 * 2 - 256, &probes);
+    }
+
+    #[test]
+    fn test_u32s_search_array() {
+        let probes = (0..500u32)
+            .map(|p| p << 23)
+            .chain([u32::MAX])
+            .collect::<Vec<_>>();
+        check_search_array::<u32, 3>(|i| (i as u32) << 30, &probes);
+        check_search_array::<u32, 16>(|i| (i as u32 * 17) << 23, &probes);
+        check_search_array::<u32, 100>(|i| (i as u32 / 2 * 3) << 23, &probes);
+        check_search_array::<u32, 129>(|i| (i as u32) << 23, &probes);
+    }
+
+    #[test]
+    fn test_i64s_search_array() {
+        let probes = (-100..100i64).map(|p| p << 50).collect::<Vec<_>>();
+        check_search_array::<i64, 1>(|_| 0, &probes);
+        check_search_array::<i64, 32>(|i| (i as i64 - 16) << 52, &probes);
+        check_search_array::<i64, 63>(|i| (i as i64 - 31) << 51, &probes);
+        check_search_array::<i64, 64>(|i| (i as i64 - 32) << 50, &probes);
+        // 65 * 8 bytes > MAX_UNROLLED_BYTES, bisected down to the unrolled window
+        check_search_array::<i64, 65>(|i| (i as i64 - 32) << 50, &probes);
+        // past the array size, plain `binary_search_auto`
+        check_search_array::<i64, 200>(|i| (i as i64 - 100) << 50, &probes);
+    }
+
+    #[test]
+    fn test_u64s_search_array() {
+        let probes = [0, 1, 2, u64::MAX - 1, u64::MAX];
+        check_search_array::<u64, 2>(|i| [1, u64::MAX][i], &probes);
+        check_search_array::<u64, 5>(|i| [0, 0, 1, u64::MAX, u64::MAX][i], &probes);
+    }
+}
@@ -0,0 +1,220 @@
+#[cfg(target_arch = "x86")]
+use std::arch::x86::*;
+#[cfg(target_arch = "x86_64")]
+use std::arch::x86_64::*;
+
+use crate::simd::linear::{load, Vector};
+
+/// `u64` words held by one `Vector`
+const VECTOR_WORDS: usize = std::mem::size_of::<Vector>() / 8;
+
+/// number of set bits in `words`
+pub fn popcount(words: &[u64]) -> u64 {
+    let mut chunks = words.chunks_exact(VECTOR_WORDS);
+    let simd = unsafe { popcount_vectors(&mut chunks) };
+    simd + chunks
+        .remainder()
+        .iter()
+        .map(|w| w.count_ones() as u64)
+        .sum::<u64>()
+}
+
+/// position of the `k`-th (0-based) set bit of `word`, `None` if it has at most `k` of them
+#[cfg(all(target_feature = "bmi2", target_arch = "x86_64"))]
+#[inline(always)]
+pub fn select_in_word(word: u64, k: u32) -> Option<u32> {
+    if k >= word.count_ones() {
+        return None;
+    }
+    // deposit a single bit onto the `k`-th one of `word`
+    Some(unsafe { _tzcnt_u64(_pdep_u64(1 << k, word)) as u32 })
+}
+
+/// position of the `k`-th (0-based) set bit of `word`, `None` if it has at most `k` of them
+#[cfg(not(all(target_feature = "bmi2", target_arch = "x86_64")))]
+#[inline(always)]
+pub fn select_in_word(word: u64, k: u32) -> Option<u32> {
+    if k >= word.count_ones() {
+        return None;
+    }
+    let mut k = k;
+    let mut shift = 0;
+    // skip whole bytes first
+    loop {
+        let ones = ((word >> shift) & 0xFF).count_ones();
+        if k < ones {
+            break;
+        }
+        k -= ones;
+        shift += 8;
+    }
+    let mut byte = (word >> shift) & 0xFF;
+    for _ in 0..k {
+        byte &= byte - 1;
+    }
+    Some(shift + byte.trailing_zeros())
+}
+
+#[cfg(all(
+    feature = "use-sse",
+    target_feature = "sse",
+    any(target_arch = "x86_64", target_arch = "x86")
+))]
+#[cfg(target_feature = "ssse3")]
+#[inline(always)]
+unsafe fn popcount_bytes(v: Vector) -> Vector {
+    // popcount of every nibble by table lookup, summed up per 64 bits
+    let lut = _mm_setr_epi8(0, 1, 1, 2, 1, 2, 2, 3, 1, 2, 2, 3, 2, 3, 3, 4);
+    let low_mask = _mm_set1_epi8(0x0F);
+    let lo = _mm_and_si128(v, low_mask);
+    let hi = _mm_and_si128(_mm_srli_epi16(v, 4), low_mask);
+    let cnt = _mm_add_epi8(_mm_shuffle_epi8(lut, lo), _mm_shuffle_epi8(lut, hi));
+    _mm_sad_epu8(cnt, _mm_setzero_si128())
+}
+
+#[cfg(all(
+    feature = "use-sse",
+    target_feature = "sse",
+    any(target_arch = "x86_64", target_arch = "x86")
+))]
+#[cfg(not(target_feature = "ssse3"))]
+#[inline(always)]
+unsafe fn popcount_bytes(v: Vector) -> Vector {
+    // SSE2 only: pairs, nibbles then bytes summed with shifts and masks, then per 64 bits
+    let pairs = _mm_sub_epi8(v, _mm_and_si128(_mm_srli_epi16(v, 1), _mm_set1_epi8(0x55)));
+    let m2 = _mm_set1_epi8(0x33);
+    let nibbles = _mm_add_epi8(
+        _mm_and_si128(pairs, m2),
+        _mm_and_si128(_mm_srli_epi16(pairs, 2), m2),
+    );
+    let cnt = _mm_and_si128(
+        _mm_add_epi8(nibbles, _mm_srli_epi16(nibbles, 4)),
+        _mm_set1_epi8(0x0F),
+    );
+    _mm_sad_epu8(cnt, _mm_setzero_si128())
+}
+
+#[cfg(all(
+    feature = "use-sse",
+    target_feature = "sse",
+    any(target_arch = "x86_64", target_arch = "x86")
+))]
+#[inline(always)]
+unsafe fn add_lanes(a: Vector, b: Vector) -> Vector {
+    _mm_add_epi64(a, b)
+}
+
+#[cfg(all(
+    feature = "use-sse",
+    target_feature = "sse",
+    any(target_arch = "x86_64", target_arch = "x86")
+))]
+#[inline(always)]
+unsafe fn zero() -> Vector {
+    _mm_setzero_si128()
+}
+
+#[cfg(all(
+    feature = "use-sse",
+    target_feature = "sse",
+    any(target_arch = "x86_64", target_arch = "x86")
+))]
+#[inline(always)]
+unsafe fn sum_lanes(v: Vector) -> u64 {
+    let mut tmp = [0u64; VECTOR_WORDS];
+    _mm_storeu_si128(tmp.as_mut_ptr() as *mut _, v);
+    tmp.iter().sum()
+}
+
+#[cfg(all(
+    feature = "use-avx2",
+    target_feature = "avx2",
+    any(target_arch = "x86_64", target_arch = "x86")
+))]
+#[inline(always)]
+unsafe fn popcount_bytes(v: Vector) -> Vector {
+    // popcount of every nibble by table lookup, summed up per 64 bits
+    let lut = _mm256_setr_epi8(
+        0, 1, 1, 2, 1, 2, 2, 3, 1, 2, 2, 3, 2, 3, 3, 4, 0, 1, 1, 2, 1, 2, 2, 3, 1, 2, 2, 3, 2, 3,
+        3, 4,
+    );
+    let low_mask = _mm256_set1_epi8(0x0F);
+    let lo = _mm256_and_si256(v, low_mask);
+    let hi = _mm256_and_si256(_mm256_srli_epi16(v, 4), low_mask);
+    let cnt = _mm256_add_epi8(_mm256_shuffle_epi8(lut, lo), _mm256_shuffle_epi8(lut, hi));
+    _mm256_sad_epu8(cnt, _mm256_setzero_si256())
+}
+
+#[cfg(all(
+    feature = "use-avx2",
+    target_feature = "avx2",
+    any(target_arch = "x86_64", target_arch = "x86")
+))]
+#[inline(always)]
+unsafe fn add_lanes(a: Vector, b: Vector) -> Vector {
+    _mm256_add_epi64(a, b)
+}
+
+#[cfg(all(
+    feature = "use-avx2",
+    target_feature = "avx2",
+    any(target_arch = "x86_64", target_arch = "x86")
+))]
+#[inline(always)]
+unsafe fn zero() -> Vector {
+    _mm256_setzero_si256()
+}
+
+#[cfg(all(
+    feature = "use-avx2",
+    target_feature = "avx2",
+    any(target_arch = "x86_64", target_arch = "x86")
+))]
+#[inline(always)]
+unsafe fn sum_lanes(v: Vector) -> u64 {
+    let mut tmp = [0u64; VECTOR_WORDS];
+    _mm256_storeu_si256(tmp.as_mut_ptr() as *mut _, v);
+    tmp.iter().sum()
+}
+
+#[inline(always)]
+unsafe fn popcount_vectors(chunks: &mut std::slice::ChunksExact<'_, u64>) -> u64 {
+    let mut acc = zero();
+    for chunk in chunks {
+        acc = add_lanes(acc, popcount_bytes(load(chunk.as_ptr())));
+    }
+    sum_lanes(acc)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::simd::bits::{popcount, select_in_word};
+
+    #[test]
+    fn test_popcount() {
+        let words = (0..100u64)
+            .map(|i| i.wrapping_mul(0x9E3779B97F4A7C15) ^ (i << 40))
+            .collect::<Vec<_>>();
+        for size in 0..words.len() {
+            let expected = words[..size]
+                .iter()
+                .map(|w| w.count_ones() as u64)
+                .sum::<u64>();
+            assert_eq!(popcount(&words[..size]), expected);
+        }
+        assert_eq!(popcount(&[u64::MAX; 9]), 64 * 9);
+    }
+
+    #[test]
+    fn test_select_in_word() {
+        assert_eq!(select_in_word(0, 0), None);
+        assert_eq!(select_in_word(1, 63), None);
+        for word in [1u64, u64::MAX, 0x8000_0000_0000_0000, 0xF0F0_0000_1234_0001] {
+            let positions = (0..64).filter(|b| word >> b & 1 == 1).collect::<Vec<_>>();
+            for (k, pos) in positions.iter().enumerate() {
+                assert_eq!(select_in_word(word, k as u32), Some(*pos));
+            }
+            assert_eq!(select_in_word(word, positions.len() as u32), None);
+        }
+    }
+}
@@ -27,6 +27,21 @@ fn avx_round<T: SIMDField>() -> usize {
     }
 }
 
+#[cfg(all(
+    feature = "use-sse",
+    target_feature = "sse",
+    any(target_arch = "x86_64", target_arch = "x86")
+))]
+pub(crate) type Vector = __m128i;
+
+#[cfg(all(
+    feature = "use-avx2",
+    target_feature = "avx2",
+    any(target_arch = "x86_64", target_arch = "x86")
+))]
+pub(crate) type Vector = __m256i;
+
+/// lanes of `T` held by one `Vector`
 #[cfg(all(
     any(feature = "use-sse", feature = "use-avx2"),
     all(
@@ -34,38 +49,58 @@ fn avx_round<T: SIMDField>() -> usize {
         any(target_arch = "x86_64", target_arch = "x86")
     )
 ))]
-pub fn linear_search<T: SIMDField>(nums: &[T], target: T) -> Option<usize> {
-    let len = nums.len();
-    let field_size = T::size_in_bits();
-    // store nums in 128 bit
+#[inline(always)]
+pub(crate) fn lanes<T: SIMDField>() -> usize {
     #[cfg(all(feature = "use-sse", target_feature = "sse"))]
-    let unit_size = sse_round::<T>();
-    #[cfg(all(feature = "use-avx2", target_feature = "avx"))]
-    let unit_size = avx_round::<T>();
-    let round = (len / unit_size * 2) / (unit_size * 2);
-    let i = 0;
-    unsafe {
-        #[cfg(all(feature = "use-sse", target_feature = "sse"))]
-        {
-            match field_size {
-                8 => linear_8bits_sse(i, round * 2, nums, target),
-                16 => linear_16bits_sse(i, unit_size, round, nums, target),
-                32 => linear_32bits_sse(i, unit_size, round, nums, target),
-                64 => linear_64bits_sse(i, unit_size, round, nums, target),
-                _ => unreachable!(),
-            }
-        }
-        #[cfg(all(feature = "use-avx2", target_feature = "avx"))]
-        {
-            match field_size {
-                8 => linear_8bits_avx(i, round * 2, nums, target),
-                16 => linear_16bits_avx(i, unit_size, round, nums, target),
-                32 => linear_32bits_avx(i, unit_size, round, nums, target),
-                64 => linear_64bits_avx(i, unit_size, round, nums, target),
-                _ => unreachable!(),
-            }
-        }
+    {
+        sse_round::<T>()
+    }
+    #[cfg(all(feature = "use-avx2", target_feature = "avx2"))]
+    {
+        avx_round::<T>()
+    }
+}
+
+/// `movemask` yields one bit per byte, keep only the lowest bit of every lane
+/// so that a set bit maps to exactly one element
+#[inline(always)]
+pub(crate) fn lane_bits<T: SIMDField>() -> u32 {
+    match T::size_in_bits() {
+        8 => 0xFFFF_FFFF,
+        16 => 0x5555_5555,
+        32 => 0x1111_1111,
+        64 => 0x0101_0101,
+        _ => unreachable!(),
+    }
+}
+
+/// bit position in a `lane_bits` mask -> lane index
+#[inline(always)]
+pub(crate) fn lane_of<T: SIMDField>(bit: u32) -> usize {
+    bit as usize / (T::size_in_bits() / 8)
+}
+
+/// flips the sign bit of unsigned lanes so that the signed `cmpgt` orders them correctly
+#[cfg(all(
+    any(feature = "use-sse", feature = "use-avx2"),
+    all(
+        any(target_feature = "sse", target_feature = "avx2"),
+        any(target_arch = "x86_64", target_arch = "x86")
+    )
+))]
+#[inline(always)]
+pub(crate) unsafe fn to_signed<T: SIMDField>(v: Vector) -> Vector {
+    if T::is_signed() {
+        return v;
     }
+    let sign = match T::size_in_bits() {
+        8 => splat(i8::MIN),
+        16 => splat(i16::MIN),
+        32 => splat(i32::MIN),
+        64 => splat(i64::MIN),
+        _ => unreachable!(),
+    };
+    xor(v, sign)
 }
 
 #[cfg(all(
@@ -74,23 +109,14 @@ pub fn linear_search<T: SIMDField>(nums: &[T], target: T) -> Option<usize> {
     any(target_arch = "x86_64", target_arch = "x86")
 ))]
 #[inline(always)]
-unsafe fn linear_8bits_sse<T: SIMDField>(
-    mut from: usize,
-    round: usize,
-    nums: &[T],
-    target: T,
-) -> Option<usize> {
-    let keys = _mm_set1_epi8(target.unchecked_i8());
-    while from < round {
-        let chunk = _mm_loadu_si128((&nums[from..]).as_ptr() as *const _);
-        let cmp0 = _mm_cmpeq_epi8(chunk, keys);
-        let mask = _mm_movemask_epi8(cmp0);
-        if mask != 0 {
-            return Some(from + mask.trailing_zeros() as usize);
-        }
-        from += 16
+pub(crate) unsafe fn splat<T: SIMDField>(v: T) -> Vector {
+    match T::size_in_bits() {
+        8 => _mm_set1_epi8(v.unchecked_i8()),
+        16 => _mm_set1_epi16(v.unchecked_i16()),
+        32 => _mm_set1_epi32(v.unchecked_i32()),
+        64 => _mm_set1_epi64x(v.unchecked_i64()),
+        _ => unreachable!(),
     }
-    linear_search_generic(nums, &target, round)
 }
 
 #[cfg(all(
@@ -99,61 +125,70 @@ unsafe fn linear_8bits_sse<T: SIMDField>(
     any(target_arch = "x86_64", target_arch = "x86")
 ))]
 #[inline(always)]
-unsafe fn linear_16bits_sse<T: SIMDField>(
-    mut from: usize,
-    unit_size: usize,
-    round: usize,
-    nums: &[T],
-    target: T,
-) -> Option<usize> {
-    let keys = _mm_set1_epi16(target.unchecked_i16());
-    let step = unit_size * 2;
-    while from < round {
-        let chunk0 = _mm_loadu_si128((&nums[from..]).as_ptr() as *const _);
-        let chunk1 = _mm_loadu_si128((&nums[from + unit_size..]).as_ptr() as *const _);
-        let cmp0 = _mm_cmpeq_epi16(chunk0, keys);
-        let cmp1 = _mm_cmpeq_epi16(chunk1, keys);
-        // vector saturating 8
-        let packed = _mm_packs_epi16(cmp0, cmp1);
-        let mask = _mm_movemask_epi8(packed);
-        if mask != 0 {
-            return Some(from + mask.trailing_zeros() as usize);
-        }
-        from += step;
+pub(crate) unsafe fn load<T: SIMDField>(ptr: *const T) -> Vector {
+    _mm_loadu_si128(ptr as *const _)
+}
+
+#[cfg(all(
+    feature = "use-sse",
+    target_feature = "sse",
+    any(target_arch = "x86_64", target_arch = "x86")
+))]
+#[inline(always)]
+pub(crate) unsafe fn sub<T: SIMDField>(a: Vector, b: Vector) -> Vector {
+    match T::size_in_bits() {
+        8 => _mm_sub_epi8(a, b),
+        16 => _mm_sub_epi16(a, b),
+        32 => _mm_sub_epi32(a, b),
+        64 => _mm_sub_epi64(a, b),
+        _ => unreachable!(),
     }
-    linear_search_generic(nums, &target, round)
 }
 
+/// sum of all bytes of `v`
 #[cfg(all(
     feature = "use-sse",
     target_feature = "sse",
     any(target_arch = "x86_64", target_arch = "x86")
 ))]
 #[inline(always)]
-unsafe fn linear_32bits_sse<T: SIMDField>(
-    mut from: usize,
-    unit_size: usize,
-    round: usize,
-    nums: &[T],
-    target: T,
-) -> Option<usize> {
-    let keys = _mm_set1_epi32(target.unchecked_i32());
-    let step = unit_size * 2;
-    while from < round {
-        let chunk0 = _mm_loadu_si128((&nums[from..]).as_ptr() as *const _);
-        let chunk1 = _mm_loadu_si128((&nums[from + unit_size..]).as_ptr() as *const _);
-        let cmp0 = _mm_cmpeq_epi32(chunk0, keys);
-        let cmp1 = _mm_cmpeq_epi32(chunk1, keys);
-        // saturating 16
-        let packed = _mm_packs_epi32(cmp0, cmp1);
-        let mask = _mm_movemask_epi8(packed);
-        if mask != 0 {
-            return Some(from + mask.trailing_zeros() as usize / 2);
-        }
+pub(crate) unsafe fn sum_bytes(v: Vector) -> u64 {
+    let sums = _mm_sad_epu8(v, _mm_setzero_si128());
+    (_mm_cvtsi128_si32(sums) as u32 + _mm_extract_epi16::<4>(sums) as u32) as u64
+}
+
+/// `ptr` must be aligned to the vector width
+#[cfg(all(
+    feature = "use-sse",
+    target_feature = "sse",
+    any(target_arch = "x86_64", target_arch = "x86")
+))]
+#[inline(always)]
+pub(crate) unsafe fn load_aligned<T: SIMDField>(ptr: *const T) -> Vector {
+    _mm_load_si128(ptr as *const _)
+}
 
-        from += step
+#[cfg(all(
+    feature = "use-sse",
+    target_feature = "sse",
+    any(target_arch = "x86_64", target_arch = "x86")
+))]
+#[inline(always)]
+pub(crate) unsafe fn cmpeq<T: SIMDField>(a: Vector, b: Vector) -> Vector {
+    match T::size_in_bits() {
+        8 => _mm_cmpeq_epi8(a, b),
+        16 => _mm_cmpeq_epi16(a, b),
+        32 => _mm_cmpeq_epi32(a, b),
+        #[cfg(target_feature = "sse4.1")]
+        64 => _mm_cmpeq_epi64(a, b),
+        // SSE2 only: both 32 bit halves must match
+        #[cfg(not(target_feature = "sse4.1"))]
+        64 => {
+            let halves = _mm_cmpeq_epi32(a, b);
+            _mm_and_si128(halves, _mm_shuffle_epi32::<0b10_11_00_01>(halves))
+        }
+        _ => unreachable!(),
     }
-    linear_search_generic(nums, &target, round)
 }
 
 #[cfg(all(
@@ -162,31 +197,80 @@ unsafe fn linear_32bits_sse<T: SIMDField>(
     any(target_arch = "x86_64", target_arch = "x86")
 ))]
 #[inline(always)]
-unsafe fn linear_64bits_sse<T: SIMDField>(
-    mut from: usize,
-    unit_size: usize,
-    round: usize,
-    nums: &[T],
-    target: T,
-) -> Option<usize> {
-    let keys = _mm_set1_epi64x(target.unchecked_i64());
-    let step = unit_size * 2;
-    while from < round {
-        let chunk0 = _mm_loadu_si128((&nums[from..]).as_ptr() as *const _);
-        let chunk1 = _mm_loadu_si128((&nums[from + unit_size..]).as_ptr() as *const _);
-        let cmp0 = _mm_cmpeq_epi64(chunk0, keys);
-        let cmp1 = _mm_cmpeq_epi64(chunk1, keys);
-        // saturating 16 because there is only 0xFFFF or 0
-        let packed = _mm_packs_epi32(cmp0, cmp1);
-        let mask = _mm_movemask_epi8(packed);
-        // 4 byte mapping result which come from comparing 64bytes
-        if mask != 0 {
-            return Some(from + mask.trailing_zeros() as usize / 4);
+pub(crate) unsafe fn movemask(v: Vector) -> u32 {
+    _mm_movemask_epi8(v) as u32
+}
+
+#[cfg(all(
+    feature = "use-sse",
+    target_feature = "sse",
+    any(target_arch = "x86_64", target_arch = "x86")
+))]
+#[inline(always)]
+pub(crate) unsafe fn or(a: Vector, b: Vector) -> Vector {
+    _mm_or_si128(a, b)
+}
+
+#[cfg(all(
+    feature = "use-sse",
+    target_feature = "sse",
+    any(target_arch = "x86_64", target_arch = "x86")
+))]
+#[inline(always)]
+pub(crate) unsafe fn and_not(a: Vector, b: Vector) -> Vector {
+    _mm_andnot_si128(a, b)
+}
+
+#[cfg(all(
+    feature = "use-sse",
+    target_feature = "sse",
+    any(target_arch = "x86_64", target_arch = "x86")
+))]
+#[inline(always)]
+pub(crate) unsafe fn xor(a: Vector, b: Vector) -> Vector {
+    _mm_xor_si128(a, b)
+}
+
+#[cfg(all(
+    feature = "use-sse",
+    target_feature = "sse",
+    any(target_arch = "x86_64", target_arch = "x86")
+))]
+#[inline(always)]
+pub(crate) unsafe fn cmpgt<T: SIMDField>(a: Vector, b: Vector) -> Vector {
+    match T::size_in_bits() {
+        8 => _mm_cmpgt_epi8(a, b),
+        16 => _mm_cmpgt_epi16(a, b),
+        32 => _mm_cmpgt_epi32(a, b),
+        #[cfg(target_feature = "sse4.2")]
+        64 => _mm_cmpgt_epi64(a, b),
+        // SSE2 only: the high halves decide unless equal, then the borrow of `b - a` does
+        #[cfg(not(target_feature = "sse4.2"))]
+        64 => {
+            let low = _mm_and_si128(_mm_cmpeq_epi32(a, b), _mm_sub_epi64(b, a));
+            let gt = _mm_or_si128(low, _mm_cmpgt_epi32(a, b));
+            _mm_shuffle_epi32::<0b11_11_01_01>(gt)
         }
+        _ => unreachable!(),
+    }
+}
 
-        from += step;
+/// one bit per lane, unlike `movemask` which yields one bit per byte
+#[cfg(all(
+    feature = "use-sse",
+    target_feature = "sse",
+    any(target_arch = "x86_64", target_arch = "x86")
+))]
+#[inline(always)]
+pub(crate) unsafe fn lane_mask<T: SIMDField>(v: Vector) -> u32 {
+    match T::size_in_bits() {
+        8 => _mm_movemask_epi8(v) as u32,
+        // saturating 8
+        16 => _mm_movemask_epi8(_mm_packs_epi16(v, _mm_setzero_si128())) as u32,
+        32 => _mm_movemask_ps(_mm_castsi128_ps(v)) as u32,
+        64 => _mm_movemask_pd(_mm_castsi128_pd(v)) as u32,
+        _ => unreachable!(),
     }
-    linear_search_generic(nums, &target, round)
 }
 
 #[cfg(all(
@@ -194,87 +278,68 @@ unsafe fn linear_64bits_sse<T: SIMDField>(
     target_feature = "avx2",
     any(target_arch = "x86_64", target_arch = "x86")
 ))]
-unsafe fn linear_8bits_avx<T: SIMDField>(
-    mut from: usize,
-    round: usize,
-    nums: &[T],
-    target: T,
-) -> Option<usize> {
-    let keys = _mm256_set1_epi8(target.unchecked_i8());
-    while from < round {
-        let chunk = _mm256_loadu_si256((&nums[from..]).as_ptr() as *const _);
-        let cmp0 = _mm256_cmpeq_epi8(chunk, keys);
-        let mask = _mm256_movemask_epi8(cmp0);
-        if mask != 0 {
-            return Some(from + mask.trailing_zeros() as usize);
-        }
-        from += 16
+#[inline(always)]
+pub(crate) unsafe fn splat<T: SIMDField>(v: T) -> Vector {
+    match T::size_in_bits() {
+        8 => _mm256_set1_epi8(v.unchecked_i8()),
+        16 => _mm256_set1_epi16(v.unchecked_i16()),
+        32 => _mm256_set1_epi32(v.unchecked_i32()),
+        64 => _mm256_set1_epi64x(v.unchecked_i64()),
+        _ => unreachable!(),
     }
-    linear_search_generic(nums, &target, round)
 }
 
-#[allow(dead_code)]
 #[cfg(all(
     feature = "use-avx2",
     target_feature = "avx2",
     any(target_arch = "x86_64", target_arch = "x86")
 ))]
-unsafe fn dump_avx_epu8(v: __m256i) {
-    let tmp = [0u8; 32];
-    _mm256_storeu_si256((&tmp[..]).as_ptr() as _, v);
-    print!("[ {:#010b} ", tmp[0]);
-    for i in 1..32 {
-        print!("| {:#010b}", tmp[i]);
-    }
-    print!(" ]\n");
+#[inline(always)]
+pub(crate) unsafe fn load<T: SIMDField>(ptr: *const T) -> Vector {
+    _mm256_loadu_si256(ptr as *const _)
 }
 
-#[allow(dead_code)]
 #[cfg(all(
     feature = "use-avx2",
     target_feature = "avx2",
     any(target_arch = "x86_64", target_arch = "x86")
 ))]
-unsafe fn dump_avx_epu16(v: __m256i) {
-    let tmp = [0u16; 16];
-    _mm256_storeu_si256((&tmp[..]).as_ptr() as _, v);
-    print!("[ {:#018b} ", tmp[0]);
-    for i in 1..16 {
-        print!("| {:#018b}", tmp[i]);
+#[inline(always)]
+pub(crate) unsafe fn sub<T: SIMDField>(a: Vector, b: Vector) -> Vector {
+    match T::size_in_bits() {
+        8 => _mm256_sub_epi8(a, b),
+        16 => _mm256_sub_epi16(a, b),
+        32 => _mm256_sub_epi32(a, b),
+        64 => _mm256_sub_epi64(a, b),
+        _ => unreachable!(),
     }
-    print!(" ]\n");
 }
 
-#[allow(dead_code)]
+/// sum of all bytes of `v`
 #[cfg(all(
     feature = "use-avx2",
     target_feature = "avx2",
     any(target_arch = "x86_64", target_arch = "x86")
 ))]
-unsafe fn dump_avx_epu32(v: __m256i) {
-    let tmp = [0u32; 8];
-    _mm256_storeu_si256((&tmp[..]).as_ptr() as _, v);
-    print!("[ {:#034b} ", tmp[0]);
-    for i in 1..8 {
-        print!("| {:#034b}", tmp[i]);
-    }
-    print!(" ]\n");
+#[inline(always)]
+pub(crate) unsafe fn sum_bytes(v: Vector) -> u64 {
+    let sums = _mm256_sad_epu8(v, _mm256_setzero_si256());
+    let sums = _mm_add_epi64(
+        _mm256_castsi256_si128(sums),
+        _mm256_extracti128_si256::<1>(sums),
+    );
+    (_mm_cvtsi128_si32(sums) as u32 + _mm_extract_epi16::<4>(sums) as u32) as u64
 }
 
-#[allow(dead_code)]
+/// `ptr` must be aligned to the vector width
 #[cfg(all(
     feature = "use-avx2",
     target_feature = "avx2",
     any(target_arch = "x86_64", target_arch = "x86")
 ))]
-unsafe fn dump_avx_epu64(v: __m256i) {
-    let tmp = [0u64; 4];
-    _mm256_storeu_si256((&tmp[..]).as_ptr() as _, v);
-    print!("[ {:#066b} ", tmp[0]);
-    for i in 1..4 {
-        print!("| {:#066b}", tmp[i]);
-    }
-    print!(" ]\n");
+#[inline(always)]
+pub(crate) unsafe fn load_aligned<T: SIMDField>(ptr: *const T) -> Vector {
+    _mm256_load_si256(ptr as *const _)
 }
 
 #[cfg(all(
@@ -282,34 +347,15 @@ unsafe fn dump_avx_epu64(v: __m256i) {
     target_feature = "avx2",
     any(target_arch = "x86_64", target_arch = "x86")
 ))]
-unsafe fn linear_16bits_avx<T: SIMDField>(
-    mut from: usize,
-    unit_size: usize,
-    round: usize,
-    nums: &[T],
-    target: T,
-) -> Option<usize> {
-    let keys = _mm256_set1_epi16(target.unchecked_i16());
-    let shuffle = _mm256_set_epi32(7, 6, 3, 2, 5, 4, 1, 0);
-    let step = unit_size * 2;
-    while from < round {
-        let chunk0 = _mm256_loadu_si256((&nums[from..]).as_ptr() as *const _);
-        let chunk1 = _mm256_loadu_si256((&nums[from + unit_size..]).as_ptr() as *const _);
-        let cmp0 = _mm256_cmpeq_epi16(chunk0, keys);
-        // [0x0000 0x0000 0000 0000] [0000 0000 0000 0000] [0xFFFF 0000 0000 0000] [0000 0000 0000 0000]
-        let cmp1 = _mm256_cmpeq_epi16(chunk1, keys);
-        // twisting, saturating vector 8
-        let packed = _mm256_packs_epi16(cmp0, cmp1);
-        let SHUFFLED = _mm256_permutevar8x32_epi32(packed, shuffle);
-        let mask = _mm256_movemask_epi8(SHUFFLED);
-        // [0 0 0 0 0 0 0 0 1 0 0 0 0 0 0 0 0] [16 0]
-        // println!("mask {:#034b}", mask);
-        if mask != 0 {
-            return Some(from + mask.trailing_zeros() as usize);
-        }
-        from += step;
+#[inline(always)]
+pub(crate) unsafe fn cmpeq<T: SIMDField>(a: Vector, b: Vector) -> Vector {
+    match T::size_in_bits() {
+        8 => _mm256_cmpeq_epi8(a, b),
+        16 => _mm256_cmpeq_epi16(a, b),
+        32 => _mm256_cmpeq_epi32(a, b),
+        64 => _mm256_cmpeq_epi64(a, b),
+        _ => unreachable!(),
     }
-    linear_search_generic(nums, &target, round)
 }
 
 #[cfg(all(
@@ -317,92 +363,476 @@ unsafe fn linear_16bits_avx<T: SIMDField>(
     target_feature = "avx2",
     any(target_arch = "x86_64", target_arch = "x86")
 ))]
-unsafe fn linear_32bits_avx<T: SIMDField>(
-    mut from: usize,
-    unit_size: usize,
-    round: usize,
-    nums: &[T],
-    target: T,
-) -> Option<usize> {
-    let keys = _mm256_set1_epi32(target.unchecked_i32());
-    let step = unit_size * 2;
-    let shuffle = _mm256_set_epi32(7, 6, 3, 2, 5, 4, 1, 0);
-    while from < round {
-        let chunk0 = _mm256_loadu_si256((&nums[from..]).as_ptr() as *const _);
-        let chunk1 = _mm256_loadu_si256((&nums[from + unit_size..]).as_ptr() as *const _);
-        let cmp0 = _mm256_cmpeq_epi32(chunk0, keys);
-        // print!("cmp0: ");
-        // dump_avx_epu32(cmp0);
-        let cmp1 = _mm256_cmpeq_epi32(chunk1, keys);
-        // print!("cmp1: ");
-        // dump_avx_epu32(cmp1);
-        // twisting, saturating 16
-        let packed = _mm256_packs_epi32(cmp0, cmp1);
-        // print!("packed: ");
-        // dump_avx_epu16(packed);
-        // let shuffled = _mm256_permute4x64_epi64::<SHUFFLE>(packed);
-        let shuffled = _mm256_permutevar8x32_epi32(packed, shuffle);
-        // print!("shuffled: ");
-        // dump_avx_epu8(shuffled);
-        let mask = _mm256_movemask_epi8(shuffled);
-        // println!("{:#034b}", mask);
-        if mask != 0 {
-            return Some(from + mask.trailing_zeros() as usize / 2);
-        }
+#[inline(always)]
+pub(crate) unsafe fn movemask(v: Vector) -> u32 {
+    _mm256_movemask_epi8(v) as u32
+}
+
+#[cfg(all(
+    feature = "use-avx2",
+    target_feature = "avx2",
+    any(target_arch = "x86_64", target_arch = "x86")
+))]
+#[inline(always)]
+pub(crate) unsafe fn or(a: Vector, b: Vector) -> Vector {
+    _mm256_or_si256(a, b)
+}
 
-        from += step
+#[cfg(all(
+    feature = "use-avx2",
+    target_feature = "avx2",
+    any(target_arch = "x86_64", target_arch = "x86")
+))]
+#[inline(always)]
+pub(crate) unsafe fn and_not(a: Vector, b: Vector) -> Vector {
+    _mm256_andnot_si256(a, b)
+}
+
+#[cfg(all(
+    feature = "use-avx2",
+    target_feature = "avx2",
+    any(target_arch = "x86_64", target_arch = "x86")
+))]
+#[inline(always)]
+pub(crate) unsafe fn xor(a: Vector, b: Vector) -> Vector {
+    _mm256_xor_si256(a, b)
+}
+
+#[cfg(all(
+    feature = "use-avx2",
+    target_feature = "avx2",
+    any(target_arch = "x86_64", target_arch = "x86")
+))]
+#[inline(always)]
+pub(crate) unsafe fn cmpgt<T: SIMDField>(a: Vector, b: Vector) -> Vector {
+    match T::size_in_bits() {
+        8 => _mm256_cmpgt_epi8(a, b),
+        16 => _mm256_cmpgt_epi16(a, b),
+        32 => _mm256_cmpgt_epi32(a, b),
+        64 => _mm256_cmpgt_epi64(a, b),
+        _ => unreachable!(),
     }
-    linear_search_generic(nums, &target, round)
 }
 
+/// one bit per lane, unlike `movemask` which yields one bit per byte
 #[cfg(all(
     feature = "use-avx2",
     target_feature = "avx2",
     any(target_arch = "x86_64", target_arch = "x86")
 ))]
-unsafe fn linear_64bits_avx<T: SIMDField>(
-    mut from: usize,
-    unit_size: usize,
-    round: usize,
-    nums: &[T],
-    target: T,
-) -> Option<usize> {
-    let keys = _mm256_set1_epi64x(target.unchecked_i64());
-    let step = unit_size * 2;
-    let shuffle = _mm256_set_epi32(7, 6, 3, 2, 5, 4, 1, 0);
-    while from < round {
-        let chunk0 = _mm256_loadu_si256((&nums[from..]).as_ptr() as *const _);
-        let chunk1 = _mm256_loadu_si256((&nums[from + unit_size..]).as_ptr() as *const _);
-        let cmp0 = _mm256_cmpeq_epi64(chunk0, keys);
-        let cmp1 = _mm256_cmpeq_epi64(chunk1, keys);
-        // 64bits saturating 16 because there is only 0xFFFF or 0
-        let packed = _mm256_packs_epi32(cmp0, cmp1);
-        let shuffled = _mm256_permutevar8x32_epi32(packed, shuffle);
-        let mask = _mm256_movemask_epi8(shuffled);
-        // 4 byte mapping result which come from comparing 64bytes
-        if mask != 0 {
-            return Some(from + mask.trailing_zeros() as usize / 4);
+#[inline(always)]
+pub(crate) unsafe fn lane_mask<T: SIMDField>(v: Vector) -> u32 {
+    match T::size_in_bits() {
+        8 => _mm256_movemask_epi8(v) as u32,
+        16 => {
+            // twisting, saturating 8: lanes 0..8 land in bits 0..8, lanes 8..16 in bits 16..24
+            let packed = _mm256_packs_epi16(v, _mm256_setzero_si256());
+            let mask = _mm256_movemask_epi8(packed) as u32;
+            (mask & 0xFF) | ((mask >> 8) & 0xFF00)
         }
+        32 => _mm256_movemask_ps(_mm256_castsi256_ps(v)) as u32,
+        64 => _mm256_movemask_pd(_mm256_castsi256_pd(v)) as u32,
+        _ => unreachable!(),
+    }
+}
+
+/// lanes `0..n` of a `lane_mask`
+#[inline(always)]
+pub(crate) fn lane_range(n: usize) -> u32 {
+    if n >= 32 {
+        u32::MAX
+    } else {
+        (1 << n) - 1
+    }
+}
+
+/// `nums` shorter than a vector, lanes past its end are zero
+#[cfg(all(
+    any(feature = "use-sse", feature = "use-avx2"),
+    all(
+        any(target_feature = "sse", target_feature = "avx2"),
+        any(target_arch = "x86_64", target_arch = "x86")
+    )
+))]
+#[inline(always)]
+pub(crate) unsafe fn load_partial<T: SIMDField>(nums: &[T]) -> Vector {
+    #[cfg(all(feature = "use-avx2", target_feature = "avx2"))]
+    {
+        let len = nums.len();
+        match T::size_in_bits() {
+            32 => {
+                let lanes = _mm256_setr_epi32(0, 1, 2, 3, 4, 5, 6, 7);
+                let mask = _mm256_cmpgt_epi32(_mm256_set1_epi32(len as i32), lanes);
+                return _mm256_maskload_epi32(nums.as_ptr() as *const _, mask);
+            }
+            64 => {
+                let lanes = _mm256_setr_epi64x(0, 1, 2, 3);
+                let mask = _mm256_cmpgt_epi64(_mm256_set1_epi64x(len as i64), lanes);
+                return _mm256_maskload_epi64(nums.as_ptr() as *const _, mask);
+            }
+            _ => {}
+        }
+    }
+    // no masked loads for narrow lanes, go through a zeroed copy
+    let mut padded = [T::zero(); 32];
+    padded[..nums.len()].copy_from_slice(nums);
+    load(padded.as_ptr())
+}
 
-        from += step;
+/// the last vector of `nums` covering `nums[from..]`, which must be shorter than a vector
+///
+/// returns the index of its first lane, the vector and the lanes of `nums[from..]`, a slice
+/// holding at least a vector overlaps the lanes before `from` instead of reading past its end
+#[cfg(all(
+    any(feature = "use-sse", feature = "use-avx2"),
+    all(
+        any(target_feature = "sse", target_feature = "avx2"),
+        any(target_arch = "x86_64", target_arch = "x86")
+    )
+))]
+#[inline(always)]
+pub(crate) unsafe fn load_tail<T: SIMDField>(nums: &[T], from: usize) -> (usize, Vector, u32) {
+    let (len, unit_size) = (nums.len(), lanes::<T>());
+    debug_assert!(from <= len && len - from < unit_size);
+    if len >= unit_size {
+        let base = len - unit_size;
+        let chunk = load(nums[base..].as_ptr());
+        (
+            base,
+            chunk,
+            lane_range(unit_size) & !lane_range(from - base),
+        )
+    } else {
+        (0, load_partial(nums), lane_range(len) & !lane_range(from))
     }
-    linear_search_generic(nums, &target, round)
 }
 
+/// vectors compared per iteration of `linear_search` before a single test of their combined mask
+const UNROLL: usize = 4;
+
+#[cfg(all(
+    any(feature = "use-sse", feature = "use-avx2"),
+    all(
+        any(target_feature = "sse", target_feature = "avx2"),
+        any(target_arch = "x86_64", target_arch = "x86")
+    )
+))]
+pub fn linear_search<T: SIMDField>(nums: &[T], target: T) -> Option<usize> {
+    let len = nums.len();
+    let unit_size = lanes::<T>();
+    let block = UNROLL * unit_size;
+    let mut from = 0;
+    unsafe {
+        let keys = splat(target);
+        while from + block <= len {
+            let ptr = nums[from..].as_ptr();
+            let mut hits = [keys; UNROLL];
+            for (i, hit) in hits.iter_mut().enumerate() {
+                *hit = cmpeq::<T>(load(ptr.add(i * unit_size)), keys);
+            }
+            let any = hits[1..].iter().fold(hits[0], |any, hit| or(any, *hit));
+            if movemask(any) != 0 {
+                // only a hit pays for locating its lane
+                for (i, hit) in hits.iter().enumerate() {
+                    let mask = lane_mask::<T>(*hit);
+                    if mask != 0 {
+                        return Some(from + i * unit_size + mask.trailing_zeros() as usize);
+                    }
+                }
+            }
+            from += block;
+        }
+        while from + unit_size <= len {
+            let mask = lane_mask::<T>(cmpeq::<T>(load(nums[from..].as_ptr()), keys));
+            if mask != 0 {
+                return Some(from + mask.trailing_zeros() as usize);
+            }
+            from += unit_size;
+        }
+        if from < len {
+            let (base, chunk, valid) = load_tail(nums, from);
+            let mask = lane_mask::<T>(cmpeq::<T>(chunk, keys)) & valid;
+            if mask != 0 {
+                return Some(base + mask.trailing_zeros() as usize);
+            }
+        }
+    }
+    None
+}
+
+/// scalar scan of `nums[from..]` for `target`
 #[inline]
 pub fn linear_search_generic<T: num::Integer + SIMDField>(
     nums: &[T],
     target: &T,
     from: usize,
 ) -> Option<usize> {
-    let mut i = from;
-    while i < nums.len() {
-        if nums[i] == *target {
-            return Some(i);
+    nums[from..]
+        .iter()
+        .position(|n| n == target)
+        .map(|i| from + i)
+}
+
+/// last index of `target` in `nums`
+#[cfg(all(
+    any(feature = "use-sse", feature = "use-avx2"),
+    all(
+        any(target_feature = "sse", target_feature = "avx2"),
+        any(target_arch = "x86_64", target_arch = "x86")
+    )
+))]
+pub fn rlinear_search<T: SIMDField>(nums: &[T], target: T) -> Option<usize> {
+    let unit_size = lanes::<T>();
+    let mut to = nums.len();
+    unsafe {
+        let keys = splat(target);
+        while to >= unit_size {
+            let from = to - unit_size;
+            let chunk = load(nums[from..].as_ptr());
+            let mask = movemask(cmpeq::<T>(chunk, keys));
+            if mask != 0 {
+                // the highest set byte belongs to the last matching lane
+                return Some(from + lane_of::<T>(31 - mask.leading_zeros()));
+            }
+            to = from;
+        }
+        if to > 0 {
+            let chunk = if nums.len() >= unit_size {
+                load(nums.as_ptr())
+            } else {
+                load_partial(nums)
+            };
+            let mask = lane_mask::<T>(cmpeq::<T>(chunk, keys)) & lane_range(to);
+            if mask != 0 {
+                return Some(31 - mask.leading_zeros() as usize);
+            }
+        }
+    }
+    None
+}
+
+/// at most this many keys are kept in registers by `linear_search_any`
+pub const MAX_BROADCAST_KEYS: usize = 8;
+
+/// first index in `nums` equal to any of `keys`
+#[cfg(all(
+    any(feature = "use-sse", feature = "use-avx2"),
+    all(
+        any(target_feature = "sse", target_feature = "avx2"),
+        any(target_arch = "x86_64", target_arch = "x86")
+    )
+))]
+pub fn linear_search_any<T: SIMDField>(nums: &[T], keys: &[T]) -> Option<usize> {
+    match keys.len() {
+        0 => None,
+        1 => linear_search(nums, keys[0]),
+        n if n <= 4 => unsafe { linear_any_broadcast::<T, 4>(nums, keys) },
+        n if n <= MAX_BROADCAST_KEYS => unsafe {
+            linear_any_broadcast::<T, MAX_BROADCAST_KEYS>(nums, keys)
+        },
+        _ => linear_any_sorted(nums, keys),
+    }
+}
+
+#[cfg(all(
+    any(feature = "use-sse", feature = "use-avx2"),
+    all(
+        any(target_feature = "sse", target_feature = "avx2"),
+        any(target_arch = "x86_64", target_arch = "x86")
+    )
+))]
+#[inline(always)]
+unsafe fn linear_any_broadcast<T: SIMDField, const N: usize>(
+    nums: &[T],
+    keys: &[T],
+) -> Option<usize> {
+    // pad with the first key, a duplicated key never changes the result
+    let mut splats = [splat(keys[0]); N];
+    for (i, key) in keys.iter().enumerate().skip(1) {
+        splats[i] = splat(*key);
+    }
+    let unit_size = lanes::<T>();
+    let len = nums.len();
+    let mut from = 0;
+    while from + unit_size <= len {
+        let chunk = load(nums[from..].as_ptr());
+        let mut hits = cmpeq::<T>(chunk, splats[0]);
+        for k in &splats[1..] {
+            hits = or(hits, cmpeq::<T>(chunk, *k));
+        }
+        let mask = movemask(hits);
+        if mask != 0 {
+            return Some(from + lane_of::<T>(mask.trailing_zeros()));
+        }
+        from += unit_size;
+    }
+    if from < len {
+        let (base, chunk, valid) = load_tail(nums, from);
+        let mut hits = cmpeq::<T>(chunk, splats[0]);
+        for k in &splats[1..] {
+            hits = or(hits, cmpeq::<T>(chunk, *k));
+        }
+        let mask = lane_mask::<T>(hits) & valid;
+        if mask != 0 {
+            return Some(base + mask.trailing_zeros() as usize);
+        }
+    }
+    None
+}
+
+// too many keys to keep in registers: a chunk is first checked against the range of the sorted
+// needles, only its lanes within that range are then probed by binary search
+#[cfg(all(
+    any(feature = "use-sse", feature = "use-avx2"),
+    all(
+        any(target_feature = "sse", target_feature = "avx2"),
+        any(target_arch = "x86_64", target_arch = "x86")
+    )
+))]
+fn linear_any_sorted<T: SIMDField>(nums: &[T], keys: &[T]) -> Option<usize> {
+    let mut needles = keys.to_vec();
+    needles.sort_unstable();
+    needles.dedup();
+    let unit_size = lanes::<T>();
+    let len = nums.len();
+    let probe = |base: usize, mut mask: u32| {
+        while mask != 0 {
+            let i = base + mask.trailing_zeros() as usize;
+            if needles.binary_search(&nums[i]).is_ok() {
+                return Some(i);
+            }
+            mask &= mask - 1;
+        }
+        None
+    };
+    let mut from = 0;
+    unsafe {
+        let lo = to_signed::<T>(splat(needles[0]));
+        let hi = to_signed::<T>(splat(needles[needles.len() - 1]));
+        // lanes with lo <= x <= hi
+        let candidates = |chunk: Vector| {
+            let x = to_signed::<T>(chunk);
+            !lane_mask::<T>(or(cmpgt::<T>(lo, x), cmpgt::<T>(x, hi))) & lane_range(unit_size)
+        };
+        while from + unit_size <= len {
+            let mask = candidates(load(nums[from..].as_ptr()));
+            if mask != 0 {
+                if let Some(i) = probe(from, mask) {
+                    return Some(i);
+                }
+            }
+            from += unit_size;
+        }
+        if from < len {
+            let (base, chunk, valid) = load_tail(nums, from);
+            return probe(base, candidates(chunk) & valid);
         }
-        i += 1;
     }
-    return None;
+    None
+}
+
+/// every index of `target` in `nums`, in ascending order
+#[cfg(all(
+    any(feature = "use-sse", feature = "use-avx2"),
+    all(
+        any(target_feature = "sse", target_feature = "avx2"),
+        any(target_arch = "x86_64", target_arch = "x86")
+    )
+))]
+pub fn find_all<T: SIMDField>(nums: &[T], target: T) -> impl Iterator<Item = usize> + '_ {
+    FindAll {
+        nums,
+        keys: unsafe { splat(target) },
+        base: 0,
+        next: 0,
+        mask: 0,
+    }
+}
+
+#[cfg(all(
+    any(feature = "use-sse", feature = "use-avx2"),
+    all(
+        any(target_feature = "sse", target_feature = "avx2"),
+        any(target_arch = "x86_64", target_arch = "x86")
+    )
+))]
+struct FindAll<'a, T> {
+    nums: &'a [T],
+    keys: Vector,
+    // first index covered by `mask`
+    base: usize,
+    // first index not loaded yet
+    next: usize,
+    // pending hits of the current chunk, one bit per lane
+    mask: u32,
+}
+
+#[cfg(all(
+    any(feature = "use-sse", feature = "use-avx2"),
+    all(
+        any(target_feature = "sse", target_feature = "avx2"),
+        any(target_arch = "x86_64", target_arch = "x86")
+    )
+))]
+impl<T: SIMDField> Iterator for FindAll<'_, T> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        let unit_size = lanes::<T>();
+        loop {
+            if self.mask != 0 {
+                let idx = self.base + self.mask.trailing_zeros() as usize;
+                // drop the lowest hit
+                self.mask &= self.mask - 1;
+                return Some(idx);
+            }
+            let len = self.nums.len();
+            if self.next >= len {
+                return None;
+            }
+            unsafe {
+                if self.next + unit_size <= len {
+                    let chunk = load(self.nums[self.next..].as_ptr());
+                    self.mask = lane_mask::<T>(cmpeq::<T>(chunk, self.keys));
+                    self.base = self.next;
+                } else {
+                    let (base, chunk, valid) = load_tail(self.nums, self.next);
+                    self.mask = lane_mask::<T>(cmpeq::<T>(chunk, self.keys)) & valid;
+                    self.base = base;
+                }
+            }
+            self.next = (self.next + unit_size).min(len);
+        }
+    }
+}
+
+/// number of elements in `nums` equal to `target`
+#[cfg(all(
+    any(feature = "use-sse", feature = "use-avx2"),
+    all(
+        any(target_feature = "sse", target_feature = "avx2"),
+        any(target_arch = "x86_64", target_arch = "x86")
+    )
+))]
+pub fn count<T: SIMDField>(nums: &[T], target: T) -> usize {
+    let len = nums.len();
+    let unit_size = lanes::<T>();
+    let mut from = 0;
+    let mut total = 0;
+    unsafe {
+        let keys = splat(target);
+        while from + unit_size <= len {
+            let chunk = load(nums[from..].as_ptr());
+            let mask = movemask(cmpeq::<T>(chunk, keys)) & lane_bits::<T>();
+            total += mask.count_ones() as usize;
+            from += unit_size;
+        }
+        if from < len {
+            let (_, chunk, valid) = load_tail(nums, from);
+            total += (lane_mask::<T>(cmpeq::<T>(chunk, keys)) & valid).count_ones() as usize;
+        }
+    }
+    total
 }
 
 #[cfg(all(
@@ -413,8 +843,10 @@ pub fn linear_search_generic<T: num::Integer + SIMDField>(
     )
 ))]
 #[cfg(test)]
+#[allow(clippy::useless_conversion, clippy::identity_op)]
 mod tests {
-    use crate::simd::linear::linear_search;
+    use crate::simd::linear::{count, find_all, linear_search, linear_search_any, rlinear_search};
+    use crate::simd::SIMDField;
 
     #[test]
     fn test_u8s_sse_bst() {
@@ -519,4 +951,342 @@ mod tests {
             }
         }
     }
+
+    // a lone hit at every position, then a second one at the end, over block and tail boundaries
+    fn check_linear_search_unrolled<T: SIMDField + std::fmt::Debug>(zero: T, hit: T) {
+        let check = |nums: &[T]| {
+            let hits = (0..nums.len())
+                .filter(|i| nums[*i] == hit)
+                .collect::<Vec<_>>();
+            assert_eq!(linear_search(nums, hit), hits.first().copied());
+            assert_eq!(rlinear_search(nums, hit), hits.last().copied());
+            assert_eq!(count(nums, hit), hits.len());
+            assert_eq!(find_all(nums, hit).collect::<Vec<_>>(), hits);
+            assert_eq!(linear_search_any(nums, &[hit, hit]), hits.first().copied());
+        };
+        for size in 0..160 {
+            let mut nums = vec![zero; size];
+            check(&nums);
+            for i in 0..size {
+                nums[i] = hit;
+                check(&nums);
+                nums[size - 1] = hit;
+                check(&nums);
+                nums[i] = zero;
+                nums[size - 1] = zero;
+            }
+        }
+    }
+
+    #[test]
+    fn test_linear_search_unrolled() {
+        check_linear_search_unrolled(0u8, u8::MAX);
+        check_linear_search_unrolled(-1i8, i8::MIN);
+        check_linear_search_unrolled(7u16, 0x8007);
+        check_linear_search_unrolled(0i16, -1);
+        check_linear_search_unrolled(1u32, 1 << 31 | 1);
+        check_linear_search_unrolled(0i32, i32::MIN);
+        // equal low halves catch a 64 bit compare that only checks 32 bits
+        check_linear_search_unrolled(5u64, 1 << 32 | 5);
+        check_linear_search_unrolled(-1i64, 0xFFFF_FFFF);
+    }
+
+    fn check_find_all<T: SIMDField + std::fmt::Debug>(nums: &[T], target: T) {
+        let expected = nums
+            .iter()
+            .enumerate()
+            .filter(|(_, n)| **n == target)
+            .map(|(i, _)| i)
+            .collect::<Vec<_>>();
+        assert_eq!(find_all(nums, target).collect::<Vec<_>>(), expected);
+        assert_eq!(count(nums, target), expected.len());
+    }
+
+    #[test]
+    fn test_u8s_find_all() {
+        for size in 0..=u8::MAX as usize {
+            let nums = (0..size).map(|i| (i % 7) as u8).collect::<Vec<_>>();
+            for target in 0..8 {
+                check_find_all(&nums, target);
+            }
+        }
+    }
+
+    #[test]
+    fn test_i8s_find_all() {
+        for size in 0..=u8::MAX as usize {
+            let nums = (0..size).map(|i| (i % 7) as i8 - 3).collect::<Vec<_>>();
+            for target in -4..4 {
+                check_find_all(&nums, target);
+            }
+        }
+    }
+
+    #[test]
+    fn test_u16s_find_all() {
+        for size in 0..=1024usize {
+            let nums = (0..size)
+                .map(|i| (i % 13) as u16 * 1000)
+                .collect::<Vec<_>>();
+            for target in 0..14 {
+                check_find_all(&nums, target * 1000);
+            }
+        }
+    }
+
+    #[test]
+    fn test_i16s_find_all() {
+        for size in 0..=1024usize {
+            let nums = (0..size).map(|i| (i % 13) as i16 - 6).collect::<Vec<_>>();
+            for target in -7..7 {
+                check_find_all(&nums, target);
+            }
+        }
+    }
+
+    #[test]
+    fn test_u32s_find_all() {
+        for size in 0..=1024usize {
+            let nums = (0..size)
+                .map(|i| (i % 5) as u32 * 70000)
+                .collect::<Vec<_>>();
+            for target in 0..6 {
+                check_find_all(&nums, target * 70000);
+            }
+        }
+    }
+
+    #[test]
+    fn test_i32s_find_all() {
+        for size in 0..=1024usize {
+            let nums = (0..size).map(|i| (i % 5) as i32 - 2).collect::<Vec<_>>();
+            for target in -3..3 {
+                check_find_all(&nums, target);
+            }
+        }
+    }
+
+    #[test]
+    fn test_u64s_find_all() {
+        for size in 0..=1024usize {
+            let nums = (0..size)
+                .map(|i| ((i % 3) as u64) << 40)
+                .collect::<Vec<_>>();
+            for target in 0..4 {
+                check_find_all(&nums, target << 40);
+            }
+        }
+    }
+
+    #[test]
+    fn test_i64s_find_all() {
+        for size in 0..=1024usize {
+            let nums = (0..size).map(|i| (i % 3) as i64 - 1).collect::<Vec<_>>();
+            for target in -2..2 {
+                check_find_all(&nums, target);
+            }
+        }
+    }
+
+    fn check_rlinear_search<T: SIMDField + std::fmt::Debug>(nums: &[T], target: T) {
+        let expected = nums.iter().rposition(|n| *n == target);
+        assert_eq!(rlinear_search(nums, target), expected);
+    }
+
+    #[test]
+    fn test_u8s_rlinear_search() {
+        for size in 0..=u8::MAX as usize {
+            let nums = (0..size).map(|i| (i % 7) as u8).collect::<Vec<_>>();
+            for target in 0..8 {
+                check_rlinear_search(&nums, target);
+            }
+        }
+    }
+
+    #[test]
+    fn test_i8s_rlinear_search() {
+        for size in 0..=u8::MAX as usize {
+            let nums = (0..size).map(|i| (i % 7) as i8 - 3).collect::<Vec<_>>();
+            for target in -4..4 {
+                check_rlinear_search(&nums, target);
+            }
+        }
+    }
+
+    #[test]
+    fn test_u16s_rlinear_search() {
+        for size in 0..=1024usize {
+            let nums = (0..size)
+                .map(|i| (i % 13) as u16 * 1000)
+                .collect::<Vec<_>>();
+            for target in 0..14 {
+                check_rlinear_search(&nums, target * 1000);
+            }
+        }
+    }
+
+    #[test]
+    fn test_i16s_rlinear_search() {
+        for size in 0..=1024usize {
+            let nums = (0..size).map(|i| (i % 13) as i16 - 6).collect::<Vec<_>>();
+            for target in -7..7 {
+                check_rlinear_search(&nums, target);
+            }
+        }
+    }
+
+    #[test]
+    fn test_u32s_rlinear_search() {
+        for size in 0..=1024usize {
+            let nums = (0..size)
+                .map(|i| (i % 5) as u32 * 70000)
+                .collect::<Vec<_>>();
+            for target in 0..6 {
+                check_rlinear_search(&nums, target * 70000);
+            }
+        }
+    }
+
+    #[test]
+    fn test_i32s_rlinear_search() {
+        for size in 0..=1024usize {
+            let nums = (0..size).map(|i| (i % 5) as i32 - 2).collect::<Vec<_>>();
+            for target in -3..3 {
+                check_rlinear_search(&nums, target);
+            }
+        }
+    }
+
+    #[test]
+    fn test_u64s_rlinear_search() {
+        for size in 0..=1024usize {
+            let nums = (0..size)
+                .map(|i| ((i % 3) as u64) << 40)
+                .collect::<Vec<_>>();
+            for target in 0..4 {
+                check_rlinear_search(&nums, target << 40);
+            }
+        }
+    }
+
+    #[test]
+    fn test_i64s_rlinear_search() {
+        for size in 0..=1024usize {
+            let nums = (0..size).map(|i| (i % 3) as i64 - 1).collect::<Vec<_>>();
+            for target in -2..2 {
+                check_rlinear_search(&nums, target);
+            }
+        }
+    }
+
+    fn check_linear_search_any<T: SIMDField + std::fmt::Debug>(nums: &[T], keys: &[T]) {
+        let expected = nums.iter().position(|n| keys.contains(n));
+        assert_eq!(linear_search_any(nums, keys), expected);
+    }
+
+    #[test]
+    fn test_u8s_linear_search_any() {
+        let keys = (0..16u8).map(|k| k * 15).collect::<Vec<_>>();
+        for size in 0..=u8::MAX {
+            let nums = (0..size).rev().collect::<Vec<_>>();
+            for n in 0..=keys.len() {
+                check_linear_search_any(&nums, &keys[..n]);
+            }
+        }
+    }
+
+    #[test]
+    fn test_i8s_linear_search_any() {
+        let keys = (0..16i16).map(|k| (k * 15 - 120) as i8).collect::<Vec<_>>();
+        for size in 0..=u8::MAX {
+            let nums = (0..size).map(|i| i as i8).collect::<Vec<_>>();
+            for n in 0..=keys.len() {
+                check_linear_search_any(&nums, &keys[..n]);
+            }
+        }
+    }
+
+    #[test]
+    fn test_u16s_linear_search_any() {
+        let keys = (0..16u16).map(|k| k * 61).collect::<Vec<_>>();
+        for size in 0..=1024u16 {
+            let nums = (0..size).rev().collect::<Vec<_>>();
+            for n in 0..=keys.len() {
+                check_linear_search_any(&nums, &keys[..n]);
+            }
+        }
+    }
+
+    #[test]
+    fn test_i16s_linear_search_any() {
+        let keys = (0..16i16).map(|k| k * 61 - 512).collect::<Vec<_>>();
+        for size in 0..=1024i16 {
+            let nums = (0..size).map(|i| i - 512).rev().collect::<Vec<_>>();
+            for n in 0..=keys.len() {
+                check_linear_search_any(&nums, &keys[..n]);
+            }
+        }
+    }
+
+    #[test]
+    fn test_u32s_linear_search_any() {
+        let keys = (0..16u32).map(|k| k * 61).collect::<Vec<_>>();
+        for size in 0..=1024u32 {
+            let nums = (0..size).rev().collect::<Vec<_>>();
+            for n in 0..=keys.len() {
+                check_linear_search_any(&nums, &keys[..n]);
+            }
+        }
+    }
+
+    #[test]
+    fn test_i32s_linear_search_any() {
+        let keys = (0..16i32).map(|k| k * 61 - 512).collect::<Vec<_>>();
+        for size in 0..=1024i32 {
+            let nums = (0..size).map(|i| i - 512).rev().collect::<Vec<_>>();
+            for n in 0..=keys.len() {
+                check_linear_search_any(&nums, &keys[..n]);
+            }
+        }
+    }
+
+    #[test]
+    fn test_u64s_linear_search_any() {
+        let keys = (0..16u64).map(|k| k * 61).collect::<Vec<_>>();
+        for size in 0..=1024u64 {
+            let nums = (0..size).rev().collect::<Vec<_>>();
+            for n in 0..=keys.len() {
+                check_linear_search_any(&nums, &keys[..n]);
+            }
+        }
+    }
+
+    #[test]
+    fn test_i64s_linear_search_any() {
+        let keys = (0..16i64).map(|k| k * 61 - 512).collect::<Vec<_>>();
+        for size in 0..=1024i64 {
+            let nums = (0..size).map(|i| i - 512).rev().collect::<Vec<_>>();
+            for n in 0..=keys.len() {
+                check_linear_search_any(&nums, &keys[..n]);
+            }
+        }
+    }
+
+    #[test]
+    fn test_linear_search_any_needle_range() {
+        // needles straddling the sign bit, most chunks fall entirely outside their range
+        let keys = (0..20u32).map(|k| (1 << 31) - 10 + k).collect::<Vec<_>>();
+        let nums = (0..3000u32)
+            .map(|i| i.wrapping_mul(2_654_435_761) >> 1)
+            .chain([(1 << 31) + 5, 3])
+            .collect::<Vec<_>>();
+        check_linear_search_any(&nums, &keys);
+        check_linear_search_any(&nums[..2999], &keys);
+        let keys = (0..12i64).map(|k| k * 3 - 20).collect::<Vec<_>>();
+        let nums = (0..501i64).map(|i| (i - 250) * 97).collect::<Vec<_>>();
+        for size in 0..nums.len() {
+            check_linear_search_any(&nums[..size], &keys);
+        }
+        check_linear_search_any(&[i64::MIN, -20, i64::MAX], &keys);
+    }
 }
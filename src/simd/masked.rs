@@ -0,0 +1,200 @@
+use crate::simd::linear::{cmpeq, lane_mask, lanes, load, load_tail, splat, Vector};
+use crate::simd::range::RangeKeys;
+use crate::simd::SIMDField;
+
+/// validity bitmap in Arrow layout: bit `offset + i`, LSB first, is set when slot `i` is valid
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Bitmap<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Bitmap<'a> {
+    pub(crate) fn new(bytes: &'a [u8], offset: usize) -> Self {
+        Bitmap { bytes, offset }
+    }
+
+    /// bits of slots `i..i + n` as a lane mask, `n` must not exceed 32
+    #[inline(always)]
+    pub(crate) fn bits(&self, i: usize, n: usize) -> u32 {
+        let bit = self.offset + i;
+        let (byte, shift) = (bit / 8, bit % 8);
+        // at most 7 + 32 bits, i.e. 5 bytes
+        let mut word = 0u64;
+        for (k, b) in self.bytes[byte..].iter().take(5).enumerate() {
+            word |= (*b as u64) << (8 * k);
+        }
+        ((word >> shift) & ((1 << n) - 1)) as u32
+    }
+}
+
+/// first valid slot equal to `target`
+pub(crate) fn linear_search_valid<T: SIMDField>(
+    nums: &[T],
+    target: T,
+    valid: Bitmap<'_>,
+) -> Option<usize> {
+    unsafe {
+        let keys = splat(target);
+        first_valid(nums, valid, |chunk| lane_mask::<T>(cmpeq::<T>(keys, chunk)))
+    }
+}
+
+/// number of valid slots equal to `target`
+pub(crate) fn count_valid<T: SIMDField>(nums: &[T], target: T, valid: Bitmap<'_>) -> usize {
+    unsafe {
+        let keys = splat(target);
+        count_masked(nums, valid, |chunk| lane_mask::<T>(cmpeq::<T>(keys, chunk)))
+    }
+}
+
+/// first valid slot with `lo <= x < hi`
+pub(crate) fn find_first_in_range_valid<T: SIMDField>(
+    nums: &[T],
+    lo: T,
+    hi: T,
+    valid: Bitmap<'_>,
+) -> Option<usize> {
+    unsafe {
+        let keys = RangeKeys::new(lo, hi);
+        first_valid(nums, valid, |chunk| keys.test::<T>(chunk))
+    }
+}
+
+/// number of valid slots with `lo <= x < hi`
+pub(crate) fn count_in_range_valid<T: SIMDField>(
+    nums: &[T],
+    lo: T,
+    hi: T,
+    valid: Bitmap<'_>,
+) -> usize {
+    unsafe {
+        let keys = RangeKeys::new(lo, hi);
+        count_masked(nums, valid, |chunk| keys.test::<T>(chunk))
+    }
+}
+
+// the compare mask of every vector is ANDed with the validity bits of its lanes
+#[inline(always)]
+unsafe fn first_valid<T: SIMDField>(
+    nums: &[T],
+    valid: Bitmap<'_>,
+    mask: impl Fn(Vector) -> u32,
+) -> Option<usize> {
+    let len = nums.len();
+    let unit_size = lanes::<T>();
+    let mut from = 0;
+    while from + unit_size <= len {
+        let m = mask(load(nums[from..].as_ptr())) & valid.bits(from, unit_size);
+        if m != 0 {
+            return Some(from + m.trailing_zeros() as usize);
+        }
+        from += unit_size;
+    }
+    if from < len {
+        let (base, chunk, tail) = load_tail(nums, from);
+        let m = mask(chunk) & tail & valid.bits(base, unit_size);
+        if m != 0 {
+            return Some(base + m.trailing_zeros() as usize);
+        }
+    }
+    None
+}
+
+#[inline(always)]
+unsafe fn count_masked<T: SIMDField>(
+    nums: &[T],
+    valid: Bitmap<'_>,
+    mask: impl Fn(Vector) -> u32,
+) -> usize {
+    let len = nums.len();
+    let unit_size = lanes::<T>();
+    let mut from = 0;
+    let mut total = 0;
+    while from + unit_size <= len {
+        total +=
+            (mask(load(nums[from..].as_ptr())) & valid.bits(from, unit_size)).count_ones() as usize;
+        from += unit_size;
+    }
+    if from < len {
+        let (base, chunk, tail) = load_tail(nums, from);
+        total += (mask(chunk) & tail & valid.bits(base, unit_size)).count_ones() as usize;
+    }
+    total
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::simd::masked::{
+        count_in_range_valid, count_valid, find_first_in_range_valid, linear_search_valid, Bitmap,
+    };
+    use crate::simd::SIMDField;
+
+    fn check_masked<T: SIMDField + std::fmt::Debug>(nums: &[T], probes: &[T], offset: usize) {
+        // every third slot past the offset is null
+        let bits = (0..nums.len() + offset)
+            .map(|i| i < offset || (i - offset) % 3 != 1)
+            .collect::<Vec<_>>();
+        let mut bytes = vec![0u8; bits.len().div_ceil(8)];
+        for (i, b) in bits.iter().enumerate() {
+            bytes[i / 8] |= (*b as u8) << (i % 8);
+        }
+        let valid = Bitmap::new(&bytes, offset);
+        let is_valid = |i: usize| bits[offset + i];
+        for (i, _) in nums.iter().enumerate() {
+            assert_eq!(valid.bits(i, 1) == 1, is_valid(i));
+        }
+        for p in probes {
+            let hits = (0..nums.len()).filter(|i| is_valid(*i) && nums[*i] == *p);
+            assert_eq!(linear_search_valid(nums, *p, valid), hits.clone().next());
+            assert_eq!(count_valid(nums, *p, valid), hits.count());
+            let hi = *p + T::one();
+            let hits = (0..nums.len()).filter(|i| is_valid(*i) && *p <= nums[*i] && nums[*i] < hi);
+            assert_eq!(
+                find_first_in_range_valid(nums, *p, hi, valid),
+                hits.clone().next()
+            );
+            assert_eq!(count_in_range_valid(nums, *p, hi, valid), hits.count());
+        }
+    }
+
+    #[test]
+    fn test_u8s_masked() {
+        let nums = (0..300u32).map(|i| (i * 7 % 13) as u8).collect::<Vec<_>>();
+        for offset in [0, 3, 8] {
+            check_masked(&nums, &(0..15).collect::<Vec<_>>(), offset);
+        }
+        // shorter than a vector, the tail is the whole slice
+        for size in [0, 1, 7, 15, 31, 33] {
+            check_masked(&nums[..size], &(0..15).collect::<Vec<_>>(), 5);
+        }
+    }
+
+    #[test]
+    fn test_i16s_masked() {
+        let nums = (0..300i16).map(|i| i * 7 % 13 - 6).collect::<Vec<_>>();
+        for offset in [0, 5] {
+            check_masked(&nums, &(-7..8).collect::<Vec<_>>(), offset);
+        }
+    }
+
+    #[test]
+    fn test_u32s_masked() {
+        let nums = (0..300u32)
+            .map(|i| i * 7 % 13 + (1 << 31))
+            .collect::<Vec<_>>();
+        check_masked(
+            &nums,
+            &(0..15).map(|i| i + (1 << 31)).collect::<Vec<_>>(),
+            7,
+        );
+    }
+
+    #[test]
+    fn test_i64s_masked() {
+        let nums = (0..300i64).map(|i| i * 7 % 13 - 6).collect::<Vec<_>>();
+        for offset in [0, 1, 13] {
+            check_masked(&nums, &(-7..8).collect::<Vec<_>>(), offset);
+        }
+    }
+}
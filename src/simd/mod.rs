@@ -1,12 +1,143 @@
-pub use linear::{linear_search, linear_search_generic};
+#[cfg(all(
+    any(feature = "use-sse", feature = "use-avx2"),
+    all(
+        any(target_feature = "sse", target_feature = "avx2"),
+        any(target_arch = "x86_64", target_arch = "x86")
+    )
+))]
+pub use array::{search_array, MAX_UNROLLED_BYTES};
+#[cfg(all(
+    any(feature = "use-sse", feature = "use-avx2"),
+    all(
+        any(target_feature = "sse", target_feature = "avx2"),
+        any(target_arch = "x86_64", target_arch = "x86")
+    )
+))]
+pub use bits::{popcount, select_in_word};
+#[cfg(all(
+    any(feature = "use-sse", feature = "use-avx2"),
+    all(
+        any(target_feature = "sse", target_feature = "avx2"),
+        any(target_arch = "x86_64", target_arch = "x86")
+    )
+))]
+pub use linear::{
+    count, find_all, linear_search, linear_search_any, linear_search_generic, rlinear_search,
+    MAX_BROADCAST_KEYS,
+};
+#[cfg(all(
+    any(feature = "use-sse", feature = "use-avx2"),
+    all(
+        any(target_feature = "sse", target_feature = "avx2"),
+        any(target_arch = "x86_64", target_arch = "x86")
+    )
+))]
+pub use nearest::{
+    ceil, ceil_ordered, floor, floor_ordered, nearest, nearest_ordered, neighbors,
+    neighbors_ordered, Tie,
+};
+#[cfg(all(
+    any(feature = "use-sse", feature = "use-avx2"),
+    all(
+        any(target_feature = "sse", target_feature = "avx2"),
+        any(target_arch = "x86_64", target_arch = "x86")
+    )
+))]
+pub use range::{count_in_range, filter_range, filter_range_values, find_first_in_range};
+#[cfg(all(
+    any(feature = "use-sse", feature = "use-avx2"),
+    all(
+        any(target_feature = "sse", target_feature = "avx2"),
+        any(target_arch = "x86_64", target_arch = "x86")
+    )
+))]
+#[cfg(feature = "rayon")]
+pub(crate) use searchsorted::search_batch;
+#[cfg(all(
+    any(feature = "use-sse", feature = "use-avx2"),
+    all(
+        any(target_feature = "sse", target_feature = "avx2"),
+        any(target_arch = "x86_64", target_arch = "x86")
+    )
+))]
+pub use searchsorted::{histogram, searchsorted, searchsorted_ordered, Side};
 pub use simd_bst::binary_search;
 
-mod linear;
+#[cfg(all(
+    any(feature = "use-sse", feature = "use-avx2"),
+    all(
+        any(target_feature = "sse", target_feature = "avx2"),
+        any(target_arch = "x86_64", target_arch = "x86")
+    )
+))]
+mod array;
+#[cfg(all(
+    any(feature = "use-sse", feature = "use-avx2"),
+    all(
+        any(target_feature = "sse", target_feature = "avx2"),
+        any(target_arch = "x86_64", target_arch = "x86")
+    )
+))]
+mod bits;
+pub(crate) mod linear;
+#[cfg(all(
+    any(feature = "use-sse", feature = "use-avx2"),
+    all(
+        any(target_feature = "sse", target_feature = "avx2"),
+        any(target_arch = "x86_64", target_arch = "x86")
+    )
+))]
+#[cfg(feature = "arrow")]
+pub(crate) mod masked;
+#[cfg(all(
+    any(feature = "use-sse", feature = "use-avx2"),
+    all(
+        any(target_feature = "sse", target_feature = "avx2"),
+        any(target_arch = "x86_64", target_arch = "x86")
+    )
+))]
+mod nearest;
+#[cfg(all(
+    any(feature = "use-sse", feature = "use-avx2"),
+    all(
+        any(target_feature = "sse", target_feature = "avx2"),
+        any(target_arch = "x86_64", target_arch = "x86")
+    )
+))]
+mod range;
+#[cfg(all(
+    any(feature = "use-sse", feature = "use-avx2"),
+    all(
+        any(target_feature = "sse", target_feature = "avx2"),
+        any(target_arch = "x86_64", target_arch = "x86")
+    )
+))]
+mod searchsorted;
 mod simd_bst;
 
+/// direction a slice is sorted in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Order {
+    Ascending,
+    Descending,
+}
+
+impl Order {
+    /// `a` sorts strictly before `b`
+    #[inline(always)]
+    pub fn before<T: SIMDField>(self, a: T, b: T) -> bool {
+        match self {
+            Order::Ascending => a < b,
+            Order::Descending => a > b,
+        }
+    }
+}
+
 pub trait SIMDField: Sized + Copy + num::Integer {
     fn size_in_bits() -> usize;
 
+    fn is_signed() -> bool;
+
     fn unchecked_i8(self) -> i8;
 
     fn unchecked_u8(self) -> u8;
@@ -22,15 +153,22 @@ pub trait SIMDField: Sized + Copy + num::Integer {
     fn unchecked_i64(self) -> i64;
 
     fn unchecked_u64(self) -> u64;
+
+    fn unchecked_from_u64(v: u64) -> Self;
 }
 
 macro_rules! simd_suit {
-    ($t:ty, $size:expr) => {
+    ($t:ty, $size:expr, $signed:expr) => {
         impl SIMDField for $t {
             fn size_in_bits() -> usize {
                 $size
             }
 
+            #[inline(always)]
+            fn is_signed() -> bool {
+                $signed
+            }
+
             #[inline(always)]
             fn unchecked_i8(self) -> i8 {
                 self as i8
@@ -70,15 +208,20 @@ macro_rules! simd_suit {
             fn unchecked_u64(self) -> u64 {
                 self as u64
             }
+
+            #[inline(always)]
+            fn unchecked_from_u64(v: u64) -> Self {
+                v as $t
+            }
         }
     };
 }
 
-simd_suit!(u8, 8);
-simd_suit!(i8, 8);
-simd_suit!(u16, 16);
-simd_suit!(i16, 16);
-simd_suit!(u32, 32);
-simd_suit!(i32, 32);
-simd_suit!(i64, 64);
-simd_suit!(u64, 64);
+simd_suit!(u8, 8, false);
+simd_suit!(i8, 8, true);
+simd_suit!(u16, 16, false);
+simd_suit!(i16, 16, true);
+simd_suit!(u32, 32, false);
+simd_suit!(i32, 32, true);
+simd_suit!(i64, 64, true);
+simd_suit!(u64, 64, false);
@@ -0,0 +1,347 @@
+use crate::simd::searchsorted::{searchsorted_ordered, Side};
+use crate::simd::{Order, SIMDField};
+
+/// which element `nearest` reports when the target lies exactly halfway between two elements
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tie {
+    /// the smaller element
+    Lower,
+    /// the larger element
+    Upper,
+}
+
+/// index of the last element not greater than `target` in the sorted `nums`
+pub fn floor<T: SIMDField>(nums: &[T], target: T) -> Option<usize> {
+    floor_ordered(nums, target, Order::Ascending)
+}
+
+/// `floor` over `nums` sorted in `order`, descending data reports the first element not
+/// greater than `target`
+pub fn floor_ordered<T: SIMDField>(nums: &[T], target: T, order: Order) -> Option<usize> {
+    match order {
+        Order::Ascending => rank(nums, target, Side::Right, order).checked_sub(1),
+        Order::Descending => {
+            let i = rank(nums, target, Side::Left, order);
+            (i < nums.len()).then_some(i)
+        }
+    }
+}
+
+/// index of the first element not less than `target` in the sorted `nums`
+pub fn ceil<T: SIMDField>(nums: &[T], target: T) -> Option<usize> {
+    ceil_ordered(nums, target, Order::Ascending)
+}
+
+/// `ceil` over `nums` sorted in `order`, descending data reports the last element not less
+/// than `target`
+pub fn ceil_ordered<T: SIMDField>(nums: &[T], target: T, order: Order) -> Option<usize> {
+    match order {
+        Order::Ascending => {
+            let i = rank(nums, target, Side::Left, order);
+            (i < nums.len()).then_some(i)
+        }
+        Order::Descending => rank(nums, target, Side::Right, order).checked_sub(1),
+    }
+}
+
+/// index of the element closest to `target` in the sorted `nums`
+///
+/// an exact hit reports the first equal element, otherwise `tie` settles equal distances
+pub fn nearest<T: SIMDField>(nums: &[T], target: T, tie: Tie) -> Option<usize> {
+    nearest_ordered(nums, target, tie, Order::Ascending)
+}
+
+/// `nearest` over `nums` sorted in `order`
+pub fn nearest_ordered<T: SIMDField>(
+    nums: &[T],
+    target: T,
+    tie: Tie,
+    order: Order,
+) -> Option<usize> {
+    let i = rank(nums, target, Side::Left, order);
+    match (i.checked_sub(1), nums.get(i)) {
+        (None, None) => None,
+        (None, Some(_)) => Some(i),
+        (Some(before), None) => Some(before),
+        (Some(_), Some(n)) if *n == target => Some(i),
+        (Some(before), Some(n)) => {
+            let (back, ahead) = (gap(nums[before], target, order), gap(target, *n, order));
+            // the element before `target` is the smaller one in ascending data
+            let lower_first = order == Order::Ascending;
+            if back < ahead || (back == ahead && (tie == Tie::Lower) == lower_first) {
+                Some(before)
+            } else {
+                Some(i)
+            }
+        }
+    }
+}
+
+/// the `k` elements closest to `target` in the sorted `nums`, fewer if `nums` is shorter
+///
+/// equal distances prefer the smaller element
+pub fn neighbors<T: SIMDField>(nums: &[T], target: T, k: usize) -> &[T] {
+    neighbors_ordered(nums, target, k, Order::Ascending)
+}
+
+/// `neighbors` over `nums` sorted in `order`
+pub fn neighbors_ordered<T: SIMDField>(nums: &[T], target: T, k: usize, order: Order) -> &[T] {
+    let k = k.min(nums.len());
+    let lower_first = order == Order::Ascending;
+    // grow `[lo, hi)` from the insertion point, one element per step
+    let mut lo = rank(nums, target, Side::Left, order);
+    let mut hi = lo;
+    while hi - lo < k {
+        let back = || gap(nums[lo - 1], target, order);
+        let ahead = || gap(target, nums[hi], order);
+        let take_back = hi == nums.len()
+            || (lo > 0 && (back() < ahead() || (back() == ahead() && lower_first)));
+        if take_back {
+            lo -= 1;
+        } else {
+            hi += 1;
+        }
+    }
+    &nums[lo..hi]
+}
+
+#[inline(always)]
+fn rank<T: SIMDField>(nums: &[T], target: T, side: Side, order: Order) -> usize {
+    let mut rank = [0];
+    searchsorted_ordered(nums, &[target], side, order, &mut rank);
+    rank[0]
+}
+
+// distance from `a` to `b` where `a` doesn't come after `b` in `order`
+#[inline(always)]
+fn gap<T: SIMDField>(a: T, b: T, order: Order) -> u64 {
+    match order {
+        Order::Ascending => distance(a, b),
+        Order::Descending => distance(b, a),
+    }
+}
+
+// `hi - lo` for `lo <= hi`, exact even where the difference overflows `T`
+#[inline(always)]
+fn distance<T: SIMDField>(lo: T, hi: T) -> u64 {
+    hi.unchecked_u64().wrapping_sub(lo.unchecked_u64())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::simd::nearest::{
+        ceil, ceil_ordered, floor, floor_ordered, nearest, nearest_ordered, neighbors,
+        neighbors_ordered, Tie,
+    };
+    use crate::simd::{Order, SIMDField};
+
+    fn gap<T: SIMDField>(a: T, b: T) -> u64 {
+        let (a, b) = (a.unchecked_i64() as i128, b.unchecked_i64() as i128);
+        let (a, b) = if T::is_signed() {
+            (a, b)
+        } else {
+            (a as u64 as i128, b as u64 as i128)
+        };
+        (a - b).unsigned_abs() as u64
+    }
+
+    fn check_nearest<T: SIMDField + std::fmt::Debug>(nums: &[T], probes: &[T]) {
+        for p in probes {
+            let below = nums.iter().rposition(|n| n <= p);
+            assert_eq!(floor(nums, *p), below);
+            let above = nums.iter().position(|n| n >= p);
+            assert_eq!(ceil(nums, *p), above);
+
+            for tie in [Tie::Lower, Tie::Upper] {
+                let expected = match (nums.iter().rposition(|n| n < p), above) {
+                    (_, Some(i)) if nums[i] == *p => Some(i),
+                    (Some(b), Some(a)) => {
+                        let (down, up) = (gap(nums[b], *p), gap(nums[a], *p));
+                        if down < up || (down == up && tie == Tie::Lower) {
+                            Some(b)
+                        } else {
+                            Some(a)
+                        }
+                    }
+                    (b, a) => b.or(a),
+                };
+                assert_eq!(nearest(nums, *p, tie), expected, "{:?} {:?}", p, tie);
+            }
+
+            for k in [0, 1, 2, 3, 7, nums.len(), nums.len() + 1] {
+                // stable sort by distance keeps the smaller element first on ties
+                let mut closest = nums.to_vec();
+                closest.sort_by_key(|n| gap(*n, *p));
+                closest.truncate(k);
+                closest.sort();
+                assert_eq!(neighbors(nums, *p, k), closest, "{:?} {}", p, k);
+            }
+        }
+        check_descending(nums, probes);
+    }
+
+    // the same probes over `nums` reversed
+    fn check_descending<T: SIMDField + std::fmt::Debug>(nums: &[T], probes: &[T]) {
+        let rev = nums.iter().rev().copied().collect::<Vec<_>>();
+        let desc = Order::Descending;
+        for p in probes {
+            let below = rev.iter().position(|n| n <= p);
+            assert_eq!(floor_ordered(&rev, *p, desc), below);
+            let above = rev.iter().rposition(|n| n >= p);
+            assert_eq!(ceil_ordered(&rev, *p, desc), above);
+
+            for tie in [Tie::Lower, Tie::Upper] {
+                let expected = match (below, rev.iter().rposition(|n| n > p)) {
+                    (Some(i), _) if rev[i] == *p => Some(i),
+                    (Some(b), Some(a)) => {
+                        let (down, up) = (gap(rev[b], *p), gap(rev[a], *p));
+                        if down < up || (down == up && tie == Tie::Lower) {
+                            Some(b)
+                        } else {
+                            Some(a)
+                        }
+                    }
+                    (b, a) => b.or(a),
+                };
+                let got = nearest_ordered(&rev, *p, tie, desc);
+                assert_eq!(got, expected, "{:?} {:?}", p, tie);
+            }
+
+            for k in [0, 1, 2, 3, 7, rev.len(), rev.len() + 1] {
+                let mut closest = nums.to_vec();
+                closest.sort_by_key(|n| gap(*n, *p));
+                closest.truncate(k);
+                closest.sort_by(|a, b| b.cmp(a));
+                assert_eq!(
+                    neighbors_ordered(&rev, *p, k, desc),
+                    closest,
+                    "{:?} {}",
+                    p,
+                    k
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_u8s_nearest() {
+        let probes = (0..=u8::MAX).collect::<Vec<_>>();
+        for size in [0u8, 1, 2, 5, 40, 120] {
+            let nums = (0..size).map(|i| i * 2 + i % 3).collect::<Vec<_>>();
+            check_nearest(&nums, &probes);
+        }
+        check_nearest(&[0, 0, 10, 10, 10, 20, u8::MAX], &probes);
+    }
+
+    #[test]
+    fn test_i8s_nearest() {
+        let probes = (i8::MIN..=i8::MAX).collect::<Vec<_>>();
+        for size in [0i8, 1, 2, 5, 40] {
+            let nums = (0..size).map(|i| i * 3 - 60 + i % 2).collect::<Vec<_>>();
+            check_nearest(&nums, &probes);
+        }
+        check_nearest(&[i8::MIN, i8::MIN, -1, 0, 0, i8::MAX], &probes);
+    }
+
+    #[test]
+    fn test_i16s_nearest() {
+        let probes = (-1200..1200i16).step_by(7).collect::<Vec<_>>();
+        let nums = (0..300i16)
+            .map(|i| i * 7 - 1000 + i % 5)
+            .collect::<Vec<_>>();
+        check_nearest(&nums, &probes);
+        check_nearest(
+            &[i16::MIN, -1, 0, i16::MAX],
+            &[i16::MIN, -2, -1, 0, 1, i16::MAX],
+        );
+    }
+
+    #[test]
+    fn test_u16s_nearest() {
+        let probes = (0..=u16::MAX).step_by(61).collect::<Vec<_>>();
+        let nums = (0..700u16).map(|i| i * 90 + i % 7).collect::<Vec<_>>();
+        check_nearest(&nums, &probes);
+        check_nearest(
+            &[0, 0, 1, u16::MAX - 1, u16::MAX],
+            &[0, 1, 2, u16::MAX / 2, u16::MAX - 1, u16::MAX],
+        );
+    }
+
+    #[test]
+    fn test_u32s_nearest() {
+        let nums = (0..500u32).map(|i| i * i * 13).collect::<Vec<_>>();
+        let probes = (0..4_000_000u32).step_by(9973).collect::<Vec<_>>();
+        check_nearest(&nums, &probes);
+        check_nearest(
+            &[0, u32::MAX],
+            &[0, u32::MAX / 2, u32::MAX / 2 + 1, u32::MAX],
+        );
+    }
+
+    #[test]
+    fn test_i32s_nearest() {
+        let nums = (-250..250i32).map(|i| i * i.abs() * 11).collect::<Vec<_>>();
+        let probes = (-800_000..800_000i32).step_by(4999).collect::<Vec<_>>();
+        check_nearest(&nums, &probes);
+        check_nearest(
+            &[i32::MIN, -1, 0, i32::MAX],
+            &[i32::MIN, i32::MIN + 1, -2, -1, 0, 1, i32::MAX - 1, i32::MAX],
+        );
+    }
+
+    #[test]
+    fn test_i64s_nearest() {
+        // timestamps with a few duplicates and irregular gaps
+        let nums = (0..400i64)
+            .map(|i| 1_700_000_000_000 + i / 3 * 1000 + i % 3 / 2 * 10)
+            .collect::<Vec<_>>();
+        let probes = (1_699_999_990_000..1_700_000_140_000i64)
+            .step_by(373)
+            .collect::<Vec<_>>();
+        check_nearest(&nums, &probes);
+        check_nearest(
+            &[i64::MIN, -5, 5, i64::MAX],
+            &[i64::MIN, -1, 0, 1, i64::MAX - 1, i64::MAX],
+        );
+    }
+
+    #[test]
+    fn test_u64s_nearest() {
+        let nums = (0..300u64).map(|i| (i << 40) | (i % 4)).collect::<Vec<_>>();
+        let probes = (0..320u64)
+            .flat_map(|i| [i << 40, (i << 40) + (1 << 39), (i << 40) + 3])
+            .collect::<Vec<_>>();
+        check_nearest(&nums, &probes);
+        check_nearest(
+            &[0, 5, u64::MAX / 2, u64::MAX],
+            &[0, 1, u64::MAX / 4, u64::MAX / 2 + 1, u64::MAX - 1, u64::MAX],
+        );
+    }
+
+    #[test]
+    fn test_nearest_ties() {
+        let nums = [10u64, 20, 30];
+        assert_eq!(nearest(&nums, 15, Tie::Lower), Some(0));
+        assert_eq!(nearest(&nums, 15, Tie::Upper), Some(1));
+        assert_eq!(nearest(&nums, 16, Tie::Lower), Some(1));
+        assert_eq!(neighbors(&nums, 25, 2), [20, 30]);
+        assert_eq!(neighbors(&nums, 15, 1), [10]);
+        assert_eq!(neighbors(&nums, 100, 2), [20, 30]);
+        assert_eq!(nearest::<u64>(&[], 1, Tie::Lower), None);
+        // newest-first timestamps
+        let desc = [30u64, 20, 10];
+        assert_eq!(
+            nearest_ordered(&desc, 15, Tie::Lower, Order::Descending),
+            Some(2)
+        );
+        assert_eq!(
+            nearest_ordered(&desc, 15, Tie::Upper, Order::Descending),
+            Some(1)
+        );
+        assert_eq!(neighbors_ordered(&desc, 25, 2, Order::Descending), [30, 20]);
+        assert_eq!(neighbors_ordered(&desc, 15, 1, Order::Descending), [10]);
+        assert_eq!(floor_ordered(&desc, 25, Order::Descending), Some(1));
+        assert_eq!(ceil_ordered(&desc, 25, Order::Descending), Some(0));
+        assert_eq!(ceil_ordered(&desc, 31, Order::Descending), None);
+    }
+}
@@ -0,0 +1,456 @@
+#[cfg(target_arch = "x86")]
+use std::arch::x86::*;
+#[cfg(target_arch = "x86_64")]
+use std::arch::x86_64::*;
+#[cfg(all(feature = "use-avx2", target_feature = "avx2"))]
+use std::mem::size_of;
+
+#[cfg(all(feature = "use-avx2", target_feature = "avx2"))]
+use crate::simd::linear::lane_range;
+use crate::simd::linear::{
+    and_not, cmpgt, lane_mask, lanes, load, load_tail, splat, to_signed, Vector,
+};
+use crate::simd::SIMDField;
+
+/// positions of the set bits of every byte, left packed
+const LEFT_PACK: [[u8; 8]; 256] = left_pack_table();
+
+const fn left_pack_table() -> [[u8; 8]; 256] {
+    let mut table = [[0u8; 8]; 256];
+    let mut mask = 0;
+    while mask < 256 {
+        let mut bit = 0;
+        let mut n = 0;
+        while bit < 8 {
+            if mask & (1 << bit) != 0 {
+                table[mask][n] = bit as u8;
+                n += 1;
+            }
+            bit += 1;
+        }
+        mask += 1;
+    }
+    table
+}
+
+/// `lo <= x < hi` evaluated with two compares per lane
+pub(crate) struct RangeKeys {
+    lo: Vector,
+    hi: Vector,
+}
+
+impl RangeKeys {
+    #[inline(always)]
+    pub(crate) unsafe fn new<T: SIMDField>(lo: T, hi: T) -> Self {
+        RangeKeys {
+            lo: to_signed::<T>(splat(lo)),
+            hi: to_signed::<T>(splat(hi)),
+        }
+    }
+
+    /// one bit per lane of `nums[from..from + lanes]`
+    #[inline(always)]
+    pub(crate) unsafe fn mask<T: SIMDField>(&self, nums: &[T], from: usize) -> u32 {
+        self.test::<T>(load(nums[from..].as_ptr()))
+    }
+
+    /// one bit per lane of `chunk`
+    #[inline(always)]
+    pub(crate) unsafe fn test<T: SIMDField>(&self, chunk: Vector) -> u32 {
+        let x = to_signed::<T>(chunk);
+        // !(lo > x) & (hi > x)
+        lane_mask::<T>(and_not(cmpgt::<T>(self.lo, x), cmpgt::<T>(self.hi, x)))
+    }
+}
+
+/// first index of `nums` with `lo <= nums[i] < hi`
+///
+/// a plain scan, `nums` may be sorted in either `Order` or not at all
+pub fn find_first_in_range<T: SIMDField>(nums: &[T], lo: T, hi: T) -> Option<usize> {
+    let len = nums.len();
+    let unit_size = lanes::<T>();
+    let mut from = 0;
+    unsafe {
+        let keys = RangeKeys::new(lo, hi);
+        while from + unit_size <= len {
+            let mask = keys.mask(nums, from);
+            if mask != 0 {
+                return Some(from + mask.trailing_zeros() as usize);
+            }
+            from += unit_size;
+        }
+        if from < len {
+            let (base, chunk, valid) = load_tail(nums, from);
+            let mask = keys.test::<T>(chunk) & valid;
+            if mask != 0 {
+                return Some(base + mask.trailing_zeros() as usize);
+            }
+        }
+    }
+    None
+}
+
+/// number of elements of `nums` with `lo <= x < hi`, in any order
+pub fn count_in_range<T: SIMDField>(nums: &[T], lo: T, hi: T) -> usize {
+    let len = nums.len();
+    let unit_size = lanes::<T>();
+    let mut from = 0;
+    let mut total = 0;
+    unsafe {
+        let keys = RangeKeys::new(lo, hi);
+        while from + unit_size <= len {
+            total += keys.mask(nums, from).count_ones() as usize;
+            from += unit_size;
+        }
+        if from < len {
+            let (_, chunk, valid) = load_tail(nums, from);
+            total += (keys.test::<T>(chunk) & valid).count_ones() as usize;
+        }
+    }
+    total
+}
+
+/// appends the index of every `lo <= x < hi` to `out`, returns how many were appended
+pub fn filter_range<T: SIMDField>(nums: &[T], lo: T, hi: T, out: &mut Vec<usize>) -> usize {
+    let start = out.len();
+    unsafe {
+        filter_range_with(nums, lo, hi, |mut base, _, mut mask| {
+            while mask != 0 {
+                let byte = (mask & 0xFF) as usize;
+                // always write 8 slots, keep only the hits
+                out.reserve(8);
+                store_indices(byte, base, out.as_mut_ptr().add(out.len()));
+                out.set_len(out.len() + byte.count_ones() as usize);
+                mask >>= 8;
+                base += 8;
+            }
+        });
+    }
+    out.len() - start
+}
+
+/// appends every `lo <= x < hi` to `out`, returns how many were appended
+pub fn filter_range_values<T: SIMDField>(nums: &[T], lo: T, hi: T, out: &mut Vec<T>) -> usize {
+    let start = out.len();
+    let unit_size = lanes::<T>();
+    unsafe {
+        filter_range_with(nums, lo, hi, |_, chunk, mask| {
+            // whole vectors are stored past the hits
+            out.reserve(2 * unit_size);
+            let n = compress_store::<T>(chunk, mask, out.as_mut_ptr().add(out.len()));
+            out.set_len(out.len() + n);
+        });
+    }
+    out.len() - start
+}
+
+// hands every vector of `nums` with its base index and the mask of its lanes in range to `pack`
+#[inline(always)]
+unsafe fn filter_range_with<T: SIMDField>(
+    nums: &[T],
+    lo: T,
+    hi: T,
+    mut pack: impl FnMut(usize, Vector, u32),
+) {
+    let len = nums.len();
+    let unit_size = lanes::<T>();
+    let keys = RangeKeys::new(lo, hi);
+    let mut from = 0;
+    while from + unit_size <= len {
+        let chunk = load(nums[from..].as_ptr());
+        pack(from, chunk, keys.test::<T>(chunk));
+        from += unit_size;
+    }
+    if from < len {
+        let (base, chunk, valid) = load_tail(nums, from);
+        pack(base, chunk, keys.test::<T>(chunk) & valid);
+    }
+}
+
+// `base + LEFT_PACK[byte]` widened to 8 indices at `dst`
+#[inline(always)]
+unsafe fn store_indices(byte: usize, base: usize, dst: *mut usize) {
+    let zero = _mm_setzero_si128();
+    let lanes = _mm_loadl_epi64(LEFT_PACK[byte].as_ptr() as *const _);
+    let words = _mm_unpacklo_epi8(lanes, zero);
+    let dwords = [
+        _mm_unpacklo_epi16(words, zero),
+        _mm_unpackhi_epi16(words, zero),
+    ];
+    #[cfg(target_pointer_width = "64")]
+    {
+        let base = _mm_set1_epi64x(base as i64);
+        for (i, d) in dwords.iter().enumerate() {
+            let lo = _mm_add_epi64(_mm_unpacklo_epi32(*d, zero), base);
+            let hi = _mm_add_epi64(_mm_unpackhi_epi32(*d, zero), base);
+            _mm_storeu_si128(dst.add(4 * i) as *mut _, lo);
+            _mm_storeu_si128(dst.add(4 * i + 2) as *mut _, hi);
+        }
+    }
+    #[cfg(target_pointer_width = "32")]
+    {
+        let base = _mm_set1_epi32(base as i32);
+        for (i, d) in dwords.iter().enumerate() {
+            _mm_storeu_si128(dst.add(4 * i) as *mut _, _mm_add_epi32(*d, base));
+        }
+    }
+}
+
+/// `pshufb` controls moving the lanes set in an 8 bit mask to the front, by lane width
+#[cfg(target_feature = "ssse3")]
+static PACK_SHUFFLES: [[[u8; 16]; 256]; 4] = [
+    pack_shuffles(1),
+    pack_shuffles(2),
+    pack_shuffles(4),
+    pack_shuffles(8),
+];
+
+#[cfg(target_feature = "ssse3")]
+const fn pack_shuffles(width: usize) -> [[u8; 16]; 256] {
+    // 0x80 zeroes the byte
+    let mut table = [[0x80u8; 16]; 256];
+    let mut mask = 0;
+    while mask < 256 {
+        let mut lane = 0;
+        let mut n = 0;
+        while lane < 8 && (lane + 1) * width <= 16 {
+            if mask & (1 << lane) != 0 {
+                let mut b = 0;
+                while b < width {
+                    table[mask][n * width + b] = (lane * width + b) as u8;
+                    b += 1;
+                }
+                n += 1;
+            }
+            lane += 1;
+        }
+        mask += 1;
+    }
+    table
+}
+
+// stores the lanes of `v` set in `mask` contiguously at `dst`, then whatever fills the vector
+#[inline(always)]
+unsafe fn compress_store<T: SIMDField>(v: Vector, mask: u32, dst: *mut T) -> usize {
+    #[cfg(all(feature = "use-sse", target_feature = "sse"))]
+    {
+        compress_half(v, mask, dst)
+    }
+    #[cfg(all(feature = "use-avx2", target_feature = "avx2"))]
+    {
+        let half = 16 / size_of::<T>();
+        let n = compress_half(_mm256_castsi256_si128(v), mask & lane_range(half), dst);
+        n + compress_half(_mm256_extracti128_si256::<1>(v), mask >> half, dst.add(n))
+    }
+}
+
+#[cfg(target_feature = "ssse3")]
+#[inline(always)]
+unsafe fn compress_half<T: SIMDField>(v: __m128i, mask: u32, dst: *mut T) -> usize {
+    let table = &PACK_SHUFFLES[T::size_in_bits().trailing_zeros() as usize - 3];
+    let shuffle =
+        |v, m: u32| _mm_shuffle_epi8(v, _mm_loadu_si128(table[m as usize].as_ptr() as *const _));
+    if T::size_in_bits() == 8 {
+        // 16 lanes, one lookup per 8
+        let (lo, hi) = (mask & 0xFF, mask >> 8);
+        let n = lo.count_ones() as usize;
+        _mm_storeu_si128(dst as *mut _, shuffle(v, lo));
+        _mm_storeu_si128(dst.add(n) as *mut _, shuffle(_mm_srli_si128::<8>(v), hi));
+        n + hi.count_ones() as usize
+    } else {
+        _mm_storeu_si128(dst as *mut _, shuffle(v, mask));
+        mask.count_ones() as usize
+    }
+}
+
+// no byte shuffle before SSSE3, the vector is spilled and its hits copied out
+#[cfg(not(target_feature = "ssse3"))]
+#[inline(always)]
+unsafe fn compress_half<T: SIMDField>(v: __m128i, mut mask: u32, dst: *mut T) -> usize {
+    let mut lanes = [T::zero(); 16];
+    _mm_storeu_si128(lanes.as_mut_ptr() as *mut _, v);
+    let mut n = 0;
+    while mask != 0 {
+        dst.add(n).write(lanes[mask.trailing_zeros() as usize]);
+        n += 1;
+        mask &= mask - 1;
+    }
+    n
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::simd::range::{
+        count_in_range, filter_range, filter_range_values, find_first_in_range,
+    };
+    use crate::simd::SIMDField;
+
+    fn check_range<T: SIMDField + std::fmt::Debug>(nums: &[T], lo: T, hi: T) {
+        let expected = nums
+            .iter()
+            .enumerate()
+            .filter(|(_, n)| lo <= **n && **n < hi)
+            .map(|(i, _)| i)
+            .collect::<Vec<_>>();
+        assert_eq!(find_first_in_range(nums, lo, hi), expected.first().copied());
+        assert_eq!(count_in_range(nums, lo, hi), expected.len());
+        let mut indices = vec![usize::MAX];
+        assert_eq!(filter_range(nums, lo, hi, &mut indices), expected.len());
+        assert_eq!(indices[0], usize::MAX);
+        assert_eq!(indices[1..], expected[..]);
+        let mut values = Vec::new();
+        assert_eq!(
+            filter_range_values(nums, lo, hi, &mut values),
+            expected.len()
+        );
+        assert_eq!(
+            values,
+            expected.iter().map(|i| nums[*i]).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_u8s_range() {
+        let nums = (0..=u8::MAX)
+            .map(|i| i.wrapping_mul(37))
+            .collect::<Vec<_>>();
+        for size in 0..nums.len() {
+            for (lo, hi) in [
+                (0, 0),
+                (0, 128),
+                (100, 200),
+                (127, 129),
+                (200, 255),
+                (255, 0),
+            ] {
+                check_range(&nums[..size], lo, hi);
+            }
+        }
+    }
+
+    #[test]
+    fn test_i8s_range() {
+        let nums = (0..=u8::MAX)
+            .map(|i| i.wrapping_mul(37) as i8)
+            .collect::<Vec<_>>();
+        for size in 0..nums.len() {
+            for (lo, hi) in [(0, 0), (-128, 0), (-50, 50), (-1, 1), (100, 127), (1, -1)] {
+                check_range(&nums[..size], lo, hi);
+            }
+        }
+    }
+
+    #[test]
+    fn test_u16s_range() {
+        let nums = (0..1024u16)
+            .map(|i| i.wrapping_mul(40503))
+            .collect::<Vec<_>>();
+        for size in 0..nums.len() {
+            for (lo, hi) in [(0, 0), (0, 0x8000), (0x7000, 0x9000), (0xF000, u16::MAX)] {
+                check_range(&nums[..size], lo, hi);
+            }
+        }
+    }
+
+    #[test]
+    fn test_i16s_range() {
+        let nums = (0..1024u16)
+            .map(|i| i.wrapping_mul(40503) as i16)
+            .collect::<Vec<_>>();
+        for size in 0..nums.len() {
+            for (lo, hi) in [(0, 0), (i16::MIN, 0), (-4096, 4096), (30000, i16::MAX)] {
+                check_range(&nums[..size], lo, hi);
+            }
+        }
+    }
+
+    #[test]
+    fn test_u32s_range() {
+        let nums = (0..1024u32)
+            .map(|i| i.wrapping_mul(2654435761))
+            .collect::<Vec<_>>();
+        for size in 0..nums.len() {
+            for (lo, hi) in [
+                (0, 0),
+                (0, 1 << 31),
+                (3 << 30, u32::MAX),
+                (1 << 30, 3 << 30),
+            ] {
+                check_range(&nums[..size], lo, hi);
+            }
+        }
+    }
+
+    #[test]
+    fn test_i32s_range() {
+        let nums = (0..1024u32)
+            .map(|i| i.wrapping_mul(2654435761) as i32)
+            .collect::<Vec<_>>();
+        for size in 0..nums.len() {
+            for (lo, hi) in [
+                (0, 0),
+                (i32::MIN, 0),
+                (-(1 << 30), 1 << 30),
+                (1 << 30, i32::MAX),
+            ] {
+                check_range(&nums[..size], lo, hi);
+            }
+        }
+    }
+
+    #[test]
+    fn test_u64s_range() {
+        let nums = (0..1024u64)
+            .map(|i| i.wrapping_mul(0x9E3779B97F4A7C15))
+            .collect::<Vec<_>>();
+        for size in 0..nums.len() {
+            for (lo, hi) in [
+                (0, 0),
+                (0, 1 << 63),
+                (3 << 62, u64::MAX),
+                (1 << 62, 3 << 62),
+            ] {
+                check_range(&nums[..size], lo, hi);
+            }
+        }
+    }
+
+    #[test]
+    fn test_i64s_range() {
+        let nums = (0..1024u64)
+            .map(|i| i.wrapping_mul(0x9E3779B97F4A7C15) as i64)
+            .collect::<Vec<_>>();
+        for size in 0..nums.len() {
+            for (lo, hi) in [
+                (0, 0),
+                (i64::MIN, 0),
+                (-(1 << 62), 1 << 62),
+                (1 << 62, i64::MAX),
+            ] {
+                check_range(&nums[..size], lo, hi);
+            }
+        }
+    }
+
+    #[test]
+    fn test_descending_range() {
+        // newest-first timestamps, the first hit is the newest one in range
+        let nums = (0..2000i64)
+            .rev()
+            .map(|i| 1_700_000_000_000 + i * 250)
+            .collect::<Vec<_>>();
+        for size in [0, 1, 3, 4, 5, 31, 1999, 2000] {
+            for (lo, hi) in [
+                (1_700_000_000_000, 1_700_000_000_001),
+                (1_700_000_100_000, 1_700_000_200_000),
+                (1_700_000_499_750, i64::MAX),
+                (0, 1_700_000_000_000),
+            ] {
+                check_range(&nums[..size], lo, hi);
+            }
+        }
+        let nums = (0..=u8::MAX).rev().collect::<Vec<_>>();
+        assert_eq!(find_first_in_range(&nums, 10, 20), Some(236));
+        assert_eq!(count_in_range(&nums, 10, 20), 10);
+    }
+}
@@ -0,0 +1,332 @@
+use crate::simd::linear::{
+    cmpgt, lane_mask, lane_range, lanes, load, load_tail, splat, to_signed, Vector,
+};
+use crate::simd::{Order, SIMDField};
+
+/// edges spanning at most this many vectors are kept in registers
+const SMALL_EDGE_VECTORS: usize = 8;
+
+/// values resolved in lockstep by the batched binary search
+const BATCH: usize = 8;
+
+/// which index `searchsorted` reports when a value equals one or more edges
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Side {
+    /// before the equal edges, the first index `i` with `value <= edges[i]` for ascending edges,
+    /// `value >= edges[i]` for descending ones
+    Left,
+    /// after the equal edges, the first index `i` with `value < edges[i]` for ascending edges,
+    /// `value > edges[i]` for descending ones
+    Right,
+}
+
+/// NumPy's `searchsorted`: `out[j]` is the insertion point of `values[j]` in the sorted `edges`
+///
+/// panics if `out` and `values` differ in length
+pub fn searchsorted<T: SIMDField>(edges: &[T], values: &[T], side: Side, out: &mut [usize]) {
+    searchsorted_ordered(edges, values, side, Order::Ascending, out)
+}
+
+/// `searchsorted` over `edges` sorted in `order`
+///
+/// panics if `out` and `values` differ in length
+pub fn searchsorted_ordered<T: SIMDField>(
+    edges: &[T],
+    values: &[T],
+    side: Side,
+    order: Order,
+    out: &mut [usize],
+) {
+    assert_eq!(
+        values.len(),
+        out.len(),
+        "searchsorted: `out` must be as long as `values`"
+    );
+    if edges.len() <= SMALL_EDGE_VECTORS * lanes::<T>() {
+        unsafe { searchsorted_small(edges, values, side, order, out) }
+    } else {
+        unsafe { searchsorted_batch(edges, values, side, order, out) }
+    }
+}
+
+/// NumPy's `histogram` with explicit bin edges
+///
+/// bin `i` counts `edges[i] <= x < edges[i + 1]`, the last bin also takes `x == edges[last]`,
+/// values outside the edges are dropped
+pub fn histogram<T: SIMDField>(edges: &[T], values: &[T]) -> Vec<u64> {
+    if edges.len() < 2 {
+        return Vec::new();
+    }
+    let bins = edges.len() - 1;
+    let last = edges[bins];
+    let mut counts = vec![0u64; bins];
+    let mut ranks = [0usize; 256];
+    for chunk in values.chunks(ranks.len()) {
+        let ranks = &mut ranks[..chunk.len()];
+        searchsorted(edges, chunk, Side::Right, ranks);
+        for (rank, value) in ranks.iter().zip(chunk) {
+            match *rank {
+                0 => {}
+                r if r <= bins => counts[r - 1] += 1,
+                // `x == edges[last]` closes the last bin
+                _ if *value == last => counts[bins - 1] += 1,
+                _ => {}
+            }
+        }
+    }
+    counts
+}
+
+/// `out[j]` is `offset` plus the first index of `targets[j]` in the sorted `region`
+#[cfg(feature = "rayon")]
+pub(crate) fn search_batch<T: SIMDField>(
+    region: &[T],
+    offset: usize,
+    targets: &[T],
+    out: &mut [Option<usize>],
+) {
+    let mut ranks = [0usize; 256];
+    // lower bound of every target, kept only on an exact hit
+    for (targets, out) in targets.chunks(ranks.len()).zip(out.chunks_mut(ranks.len())) {
+        let ranks = &mut ranks[..targets.len()];
+        searchsorted(region, targets, Side::Left, ranks);
+        for ((rank, target), hit) in ranks.iter().zip(targets).zip(out.iter_mut()) {
+            *hit = match region.get(*rank) {
+                Some(n) if n == target => Some(offset + rank),
+                _ => None,
+            };
+        }
+    }
+}
+
+// edges live in registers, every value is one broadcast + compare + popcount per vector
+unsafe fn searchsorted_small<T: SIMDField>(
+    edges: &[T],
+    values: &[T],
+    side: Side,
+    order: Order,
+    out: &mut [usize],
+) {
+    let len = edges.len();
+    if len == 0 {
+        out.fill(0);
+        return;
+    }
+    let unit_size = lanes::<T>();
+    let (full, vectors) = (len / unit_size, len.div_ceil(unit_size));
+    let mut regs = [splat(edges[0]); SMALL_EDGE_VECTORS];
+    let mut valid = [lane_range(unit_size); SMALL_EDGE_VECTORS];
+    for (i, reg) in regs.iter_mut().enumerate().take(full) {
+        *reg = to_signed::<T>(load(edges[i * unit_size..].as_ptr()));
+    }
+    if full < vectors {
+        // the tail overlaps the previous vector, its mask keeps only the lanes past it
+        let (_, chunk, mask) = load_tail(edges, full * unit_size);
+        regs[full] = to_signed::<T>(chunk);
+        valid[full] = mask;
+    }
+    let regs = &regs[..vectors];
+    let valid = &valid[..vectors];
+    for (value, rank) in values.iter().zip(out.iter_mut()) {
+        let keys = to_signed::<T>(splat(*value));
+        let mut total = 0;
+        for (edge, valid) in regs.iter().zip(valid) {
+            total += (preceding::<T>(*edge, keys, side, order) & valid).count_ones() as usize;
+        }
+        *rank = total;
+    }
+}
+
+// branchless bisection in lockstep over a batch of values until the window fits in one vector,
+// then a single compare + popcount per value
+unsafe fn searchsorted_batch<T: SIMDField>(
+    edges: &[T],
+    values: &[T],
+    side: Side,
+    order: Order,
+    out: &mut [usize],
+) {
+    let len = edges.len();
+    let unit_size = lanes::<T>();
+    for (values, out) in values.chunks(BATCH).zip(out.chunks_mut(BATCH)) {
+        let mut base = [0usize; BATCH];
+        // the window shrinks identically for every value of the batch
+        let mut n = len;
+        while n > unit_size {
+            let half = n / 2;
+            for (base, value) in base.iter_mut().zip(values) {
+                let pivot = edges[*base + half];
+                let right = match side {
+                    Side::Left => order.before(pivot, *value),
+                    Side::Right => !order.before(*value, pivot),
+                };
+                *base += if right { half } else { 0 };
+            }
+            n -= half;
+        }
+        for ((base, value), rank) in base.iter().zip(values).zip(out.iter_mut()) {
+            // the answer lies in [base, base + n], any full vector covering it will do
+            let from = (*base).min(len - unit_size);
+            let window = to_signed::<T>(load(edges[from..].as_ptr()));
+            let keys = to_signed::<T>(splat(*value));
+            let mask = preceding::<T>(window, keys, side, order) & lane_range(unit_size);
+            *rank = from + mask.count_ones() as usize;
+        }
+    }
+}
+
+// lanes of `edges` that come before the broadcast value in `keys`, both already `to_signed`
+#[inline(always)]
+unsafe fn preceding<T: SIMDField>(edges: Vector, keys: Vector, side: Side, order: Order) -> u32 {
+    match (side, order) {
+        // edges < value
+        (Side::Left, Order::Ascending) => lane_mask::<T>(cmpgt::<T>(keys, edges)),
+        // edges <= value
+        (Side::Right, Order::Ascending) => !lane_mask::<T>(cmpgt::<T>(edges, keys)),
+        // edges > value
+        (Side::Left, Order::Descending) => lane_mask::<T>(cmpgt::<T>(edges, keys)),
+        // edges >= value
+        (Side::Right, Order::Descending) => !lane_mask::<T>(cmpgt::<T>(keys, edges)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::simd::searchsorted::{histogram, searchsorted, searchsorted_ordered, Side};
+    use crate::simd::{Order, SIMDField};
+
+    fn check_searchsorted<T: SIMDField + std::fmt::Debug>(edges: &[T], values: &[T]) {
+        let mut out = vec![0; values.len()];
+        searchsorted(edges, values, Side::Left, &mut out);
+        let left = values
+            .iter()
+            .map(|v| edges.partition_point(|e| e < v))
+            .collect::<Vec<_>>();
+        assert_eq!(out, left);
+        searchsorted(edges, values, Side::Right, &mut out);
+        let right = values
+            .iter()
+            .map(|v| edges.partition_point(|e| e <= v))
+            .collect::<Vec<_>>();
+        assert_eq!(out, right);
+
+        let edges = edges.iter().rev().copied().collect::<Vec<_>>();
+        searchsorted_ordered(&edges, values, Side::Left, Order::Descending, &mut out);
+        let left = values
+            .iter()
+            .map(|v| edges.partition_point(|e| e > v))
+            .collect::<Vec<_>>();
+        assert_eq!(out, left);
+        searchsorted_ordered(&edges, values, Side::Right, Order::Descending, &mut out);
+        let right = values
+            .iter()
+            .map(|v| edges.partition_point(|e| e >= v))
+            .collect::<Vec<_>>();
+        assert_eq!(out, right);
+    }
+
+    fn check_histogram<T: SIMDField + std::fmt::Debug>(edges: &[T], values: &[T]) {
+        let mut expected = vec![0u64; edges.len().saturating_sub(1)];
+        for v in values {
+            for i in 0..expected.len() {
+                let last = i + 2 == edges.len();
+                if edges[i] <= *v && (*v < edges[i + 1] || (last && *v == edges[i + 1])) {
+                    expected[i] += 1;
+                    break;
+                }
+            }
+        }
+        assert_eq!(histogram(edges, values), expected);
+    }
+
+    #[test]
+    fn test_u8s_searchsorted() {
+        let values = (0..=u8::MAX).collect::<Vec<_>>();
+        for size in 0..=u8::MAX {
+            let edges = (0..size).map(|i| i / 3 * 3 + 1).collect::<Vec<_>>();
+            check_searchsorted(&edges, &values);
+        }
+    }
+
+    #[test]
+    fn test_i8s_searchsorted() {
+        let values = (i8::MIN..=i8::MAX).collect::<Vec<_>>();
+        for size in 0..=u8::MAX {
+            let edges = (0..size)
+                .map(|i| ((i / 3 * 3) as i16 - 126) as i8)
+                .collect::<Vec<_>>();
+            check_searchsorted(&edges, &values);
+        }
+    }
+
+    #[test]
+    fn test_u16s_searchsorted() {
+        let values = (0..2100u16).map(|v| v * 31).collect::<Vec<_>>();
+        for size in 0..=1024u16 {
+            let edges = (0..size).map(|i| i / 2 * 64 + 0x7F00).collect::<Vec<_>>();
+            check_searchsorted(&edges, &values);
+        }
+    }
+
+    #[test]
+    fn test_i16s_searchsorted() {
+        let values = (-1100..1100i16).collect::<Vec<_>>();
+        for size in 0..=1024i16 {
+            let edges = (0..size).map(|i| i / 2 * 2 - 512).collect::<Vec<_>>();
+            check_searchsorted(&edges, &values);
+        }
+    }
+
+    #[test]
+    fn test_u32s_searchsorted() {
+        let values = (0..2100u32).map(|v| v << 20).collect::<Vec<_>>();
+        for size in 0..=1024u32 {
+            let edges = (0..size).map(|i| (i / 2) << 22).collect::<Vec<_>>();
+            check_searchsorted(&edges, &values);
+        }
+    }
+
+    #[test]
+    fn test_i32s_searchsorted() {
+        let values = (-1100..1100i32).collect::<Vec<_>>();
+        for size in 0..=1024i32 {
+            let edges = (0..size).map(|i| i / 2 * 2 - 512).collect::<Vec<_>>();
+            check_searchsorted(&edges, &values);
+        }
+    }
+
+    #[test]
+    fn test_u64s_searchsorted() {
+        let values = (0..2100u64).map(|v| v << 52).collect::<Vec<_>>();
+        for size in 0..=1024u64 {
+            let edges = (0..size).map(|i| (i / 2) << 54).collect::<Vec<_>>();
+            check_searchsorted(&edges, &values);
+        }
+    }
+
+    #[test]
+    fn test_i64s_searchsorted() {
+        let values = (-1100..1100i64).collect::<Vec<_>>();
+        for size in 0..=1024i64 {
+            let edges = (0..size).map(|i| i / 2 * 2 - 512).collect::<Vec<_>>();
+            check_searchsorted(&edges, &values);
+        }
+    }
+
+    #[test]
+    fn test_histogram() {
+        let values = (0..=u8::MAX).collect::<Vec<_>>();
+        for size in 0..64u8 {
+            let edges = (0..size).map(|i| i * 4 + 3).collect::<Vec<_>>();
+            check_histogram(&edges, &values);
+        }
+        let values = (-3000..3000i32).collect::<Vec<_>>();
+        for size in [0, 1, 2, 3, 17, 300, 1000] {
+            let edges = (0..size).map(|i| i * 5 - 2000).collect::<Vec<_>>();
+            check_histogram(&edges, &values);
+        }
+        let values = (0..3000u64).map(|v| v << 50).collect::<Vec<_>>();
+        let edges = (0..500u64).map(|i| (i * 7) << 50).collect::<Vec<_>>();
+        check_histogram(&edges, &values);
+    }
+}
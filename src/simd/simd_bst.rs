@@ -1,33 +1,15 @@
-#[cfg(target_arch = "x86")]
-use std::arch::x86::*;
-#[cfg(target_arch = "x86_64")]
-use std::arch::x86_64::*;
+use std::hint::select_unpredictable;
 
-use crate::simd::SIMDField;
+use crate::simd::linear::{cmpeq, lane_mask, lanes, linear_search, load, splat};
+use crate::simd::{Order, SIMDField};
 
-#[cfg(all(
-    any(feature = "use-sse", feature = "use-avx2"),
-    all(
-        any(target_feature = "sse", target_feature = "avx2"),
-        any(target_arch = "x86_64", target_arch = "x86")
-    )
-))]
-pub fn binary_search<T: SIMDField>(nums: &[T], target: T) -> Option<usize> {
-    let end = nums.len() - 1;
-    let left = 0;
-    let right = end;
-    let field_size = T::size_in_bits();
-    unsafe {
-        match field_size {
-            8 => bst_8bits(nums, target, left, right),
-            16 => bst_16bits(nums, target, left, right),
-            32 => bst_32bits(nums, target, left, right),
-            64 => bst_64bits(nums, target, left, right),
-            _ => unreachable!(),
-        }
-    }
-}
+/// vectors covered by the final scan, bisection stops once the window fits in them
+const WINDOW_VECTORS: usize = 2;
 
+/// index of the first element equal to `target` in `nums` sorted in `order`
+///
+/// a branchless bisection narrows the window down to `WINDOW_VECTORS` vectors, which a single
+/// SIMD compare then resolves
 #[cfg(all(
     any(feature = "use-sse", feature = "use-avx2"),
     all(
@@ -35,313 +17,45 @@ pub fn binary_search<T: SIMDField>(nums: &[T], target: T) -> Option<usize> {
         any(target_arch = "x86_64", target_arch = "x86")
     )
 ))]
-unsafe fn bst_8bits<T: SIMDField>(
-    nums: &[T],
-    target: T,
-    mut left: usize,
-    mut right: usize,
-) -> Option<usize> {
-    #[cfg(all(feature = "use-sse", target_feature = "sse"))]
-    let keys = _mm_set1_epi8(target.unchecked_i8());
-    #[cfg(all(feature = "use-avx2", target_feature = "avx2"))]
-    let keys = _mm256_set1_epi8(target.unchecked_i8());
-    let end = nums.len() - 1;
-    while left <= right {
-        let pivot = (left + right) >> 1;
-        if nums[pivot] == target {
-            return Some(pivot);
-        }
-        if target < nums[pivot] {
-            right = pivot - 1;
-            #[cfg(all(feature = "use-sse", target_feature = "sse"))]
-            {
-                if right >= 16 {
-                    let v = _mm_loadu_si128((&nums[right - 16..]).as_ptr() as *const _);
-                    let v = _mm_cmpeq_epi8(v, keys);
-                    let mask = _mm_movemask_epi8(v);
-                    if mask != 0 {
-                        return Some(right - 16 + mask.trailing_zeros() as usize);
-                    }
-                }
-            }
-            #[cfg(all(feature = "use-avx2", target_feature = "avx2"))]
-            {
-                if right >= 32 {
-                    let v = _mm256_loadu_si256((&nums[right - 32..]).as_ptr() as *const _);
-                    let v = _mm256_cmpeq_epi8(v, keys);
-                    let mask = _mm256_movemask_epi8(v);
-                    if mask != 0 {
-                        return Some(right - 32 + mask.trailing_zeros() as usize);
-                    }
-                }
-            }
-        } else {
-            left = pivot + 1;
-            #[cfg(all(feature = "use-sse", target_feature = "sse"))]
-            {
-                if left + 16 < end {
-                    let v = _mm_loadu_si128((&nums[left..]).as_ptr() as *const _);
-                    let v = _mm_cmpeq_epi8(v, keys);
-                    let mask = _mm_movemask_epi8(v);
-                    if mask != 0 {
-                        return Some(left + mask.trailing_zeros() as usize);
-                    }
-                }
-            }
-            #[cfg(all(feature = "use-avx2", target_feature = "avx2"))]
-            {
-                if left + 32 < end {
-                    let v = _mm256_loadu_si256((&nums[left..]).as_ptr() as *const _);
-                    let v = _mm256_cmpeq_epi8(v, keys);
-                    let mask = _mm256_movemask_epi8(v);
-                    if mask != 0 {
-                        return Some(left + mask.trailing_zeros() as usize);
-                    }
-                }
-            }
-        }
+pub fn binary_search<T: SIMDField>(nums: &[T], target: T, order: Order) -> Option<usize> {
+    let unit_size = lanes::<T>();
+    let window = WINDOW_VECTORS * unit_size;
+    let len = nums.len();
+    if len < window {
+        return linear_search(nums, target);
     }
-    None
-}
-
-#[cfg(all(
-    any(feature = "use-sse", feature = "use-avx2"),
-    all(
-        any(target_feature = "sse", target_feature = "avx2"),
-        any(target_arch = "x86_64", target_arch = "x86")
-    )
-))]
-unsafe fn bst_16bits<T: SIMDField>(
-    nums: &[T],
-    target: T,
-    mut left: usize,
-    mut right: usize,
-) -> Option<usize> {
-    #[cfg(all(feature = "use-sse", target_feature = "sse"))]
-    let keys = _mm_set1_epi16(target.unchecked_i16());
-    #[cfg(all(feature = "use-avx2", target_feature = "avx2"))]
-    let keys = _mm256_set1_epi16(target.unchecked_i16());
-    let end = nums.len() - 1;
-    while left <= right {
-        let pivot = (left + right) >> 1;
-        if nums[pivot] == target {
-            return Some(pivot);
-        }
-        if target < nums[pivot] {
-            right = pivot - 1;
-            #[cfg(all(feature = "use-sse", target_feature = "sse"))]
-            {
-                if right >= 8 {
-                    let v = _mm_loadu_si128((&nums[right - 8..]).as_ptr() as *const _);
-                    let v = _mm_cmpeq_epi16(v, keys);
-                    let mask = _mm_movemask_epi8(v);
-                    if mask != 0 {
-                        return Some(right - 8 + mask.trailing_zeros() as usize / 2);
-                    }
-                }
-            }
-            #[cfg(all(feature = "use-avx2", target_feature = "avx2"))]
-            {
-                if right >= 16 {
-                    let v = _mm256_loadu_si256((&nums[right - 16..]).as_ptr() as *const _);
-                    let v = _mm256_cmpeq_epi16(v, keys);
-                    let mask = _mm256_movemask_epi8(v);
-                    if mask != 0 {
-                        return Some(right - 16 + mask.trailing_zeros() as usize / 2);
-                    }
-                }
-            }
-        } else {
-            left = pivot + 1;
-            #[cfg(all(feature = "use-sse", target_feature = "sse"))]
-            {
-                if left + 8 < end {
-                    let v = _mm_loadu_si128((&nums[left..]).as_ptr() as *const _);
-                    let v = _mm_cmpeq_epi16(v, keys);
-                    let mask = _mm_movemask_epi8(v);
-                    if mask != 0 {
-                        return Some(left + mask.trailing_zeros() as usize / 2);
-                    }
-                }
-            }
-            #[cfg(all(feature = "use-avx2", target_feature = "avx2"))]
-            {
-                if left + 16 < end {
-                    let v = _mm256_loadu_si256((&nums[left..]).as_ptr() as *const _);
-                    let v = _mm256_cmpeq_epi16(v, keys);
-                    let mask = _mm256_movemask_epi8(v);
-                    if mask != 0 {
-                        return Some(left + mask.trailing_zeros() as usize / 2);
-                    }
-                }
-            }
-        }
+    // the first element not before `target` lies in [base, base + n]
+    let (mut base, mut n) = (0, len);
+    while n >= window {
+        let half = n / 2;
+        base = select_unpredictable(order.before(nums[base + half], target), base + half, base);
+        n -= half;
     }
-    None
-}
-
-#[cfg(all(
-    any(feature = "use-sse", feature = "use-avx2"),
-    all(
-        any(target_feature = "sse", target_feature = "avx2"),
-        any(target_arch = "x86_64", target_arch = "x86")
-    )
-))]
-unsafe fn bst_32bits<T: SIMDField>(
-    nums: &[T],
-    target: T,
-    mut left: usize,
-    mut right: usize,
-) -> Option<usize> {
-    #[cfg(all(feature = "use-sse", target_feature = "sse"))]
-    let keys = _mm_set1_epi32(target.unchecked_i32());
-    #[cfg(all(feature = "use-avx2", target_feature = "avx2"))]
-    let keys = _mm256_set1_epi32(target.unchecked_i32());
-    let end = nums.len() - 1;
-    while left <= right {
-        let pivot = (left + right) >> 1;
-        if nums[pivot] == target {
-            return Some(pivot);
-        }
-        if target < nums[pivot] {
-            right = pivot - 1;
-            #[cfg(all(feature = "use-sse", target_feature = "sse"))]
-            {
-                if right >= 4 {
-                    let v = _mm_loadu_si128((&nums[right - 4..]).as_ptr() as *const _);
-                    let v = _mm_cmpeq_epi32(v, keys);
-                    let mask = _mm_movemask_epi8(v);
-                    if mask != 0 {
-                        return Some(right - 4 + mask.trailing_zeros() as usize / 4);
-                    }
-                }
-            }
-            #[cfg(all(feature = "use-avx2", target_feature = "avx2"))]
-            {
-                if right >= 8 {
-                    let v = _mm256_loadu_si256((&nums[right - 8..]).as_ptr() as *const _);
-                    let v = _mm256_cmpeq_epi32(v, keys);
-                    let mask = _mm256_movemask_epi8(v);
-                    if mask != 0 {
-                        return Some(right - 8 + mask.trailing_zeros() as usize / 4);
-                    }
-                }
-            }
-        } else {
-            left = pivot + 1;
-            #[cfg(all(feature = "use-sse", target_feature = "sse"))]
-            {
-                if left + 4 < end {
-                    let v = _mm_loadu_si128((&nums[left..]).as_ptr() as *const _);
-                    let v = _mm_cmpeq_epi32(v, keys);
-                    let mask = _mm_movemask_epi8(v);
-                    if mask != 0 {
-                        return Some(left + mask.trailing_zeros() as usize / 4);
-                    }
-                }
-            }
-            #[cfg(all(feature = "use-avx2", target_feature = "avx2"))]
-            {
-                if left + 8 < end {
-                    let v = _mm256_loadu_si256((&nums[left..]).as_ptr() as *const _);
-                    let v = _mm256_cmpeq_epi32(v, keys);
-                    let mask = _mm256_movemask_epi8(v);
-                    if mask != 0 {
-                        return Some(left + mask.trailing_zeros() as usize / 4);
-                    }
-                }
-            }
-        }
-    }
-    None
-}
-
-#[cfg(all(
-    any(feature = "use-sse", feature = "use-avx2"),
-    all(
-        any(target_feature = "sse", target_feature = "avx2"),
-        any(target_arch = "x86_64", target_arch = "x86")
-    )
-))]
-unsafe fn bst_64bits<T: SIMDField>(
-    nums: &[T],
-    target: T,
-    mut left: usize,
-    mut right: usize,
-) -> Option<usize> {
-    #[cfg(all(feature = "use-sse", target_feature = "sse"))]
-    let keys = _mm_set1_epi64x(target.unchecked_i64());
-    #[cfg(all(feature = "use-avx2", target_feature = "avx2"))]
-    let keys = _mm256_set1_epi64x(target.unchecked_i64());
-    let end = nums.len() - 1;
-    while left <= right {
-        let pivot = (left + right) >> 1;
-        if nums[pivot] == target {
-            return Some(pivot);
-        }
-        if target < nums[pivot] {
-            right = pivot - 1;
-            #[cfg(all(feature = "use-sse", target_feature = "sse"))]
-            {
-                if right >= 2 {
-                    let v = _mm_loadu_si128((&nums[right - 2..]).as_ptr() as *const _);
-                    let v = _mm_cmpeq_epi64(v, keys);
-                    let mask = _mm_movemask_epi8(v);
-                    if mask != 0 {
-                        return Some(right - 2 + mask.trailing_zeros() as usize / 8);
-                    }
-                }
-            }
-            #[cfg(all(feature = "use-avx2", target_feature = "avx2"))]
-            {
-                if right >= 4 {
-                    let v = _mm256_loadu_si256((&nums[right - 4..]).as_ptr() as *const _);
-                    let v = _mm256_cmpeq_epi64(v, keys);
-                    let mask = _mm256_movemask_epi8(v);
-                    if mask != 0 {
-                        return Some(right - 4 + mask.trailing_zeros() as usize / 8);
-                    }
-                }
-            }
-        } else {
-            left = pivot + 1;
-            #[cfg(all(feature = "use-sse", target_feature = "sse"))]
-            {
-                if left + 2 < end {
-                    let v = _mm_loadu_si128((&nums[left..]).as_ptr() as *const _);
-                    let v = _mm_cmpeq_epi64(v, keys);
-                    let mask = _mm_movemask_epi8(v);
-                    if mask != 0 {
-                        return Some(left + mask.trailing_zeros() as usize / 8);
-                    }
-                }
-            }
-            #[cfg(all(feature = "use-avx2", target_feature = "avx2"))]
-            {
-                if left + 4 < end {
-                    let v = _mm256_loadu_si256((&nums[left..]).as_ptr() as *const _);
-                    let v = _mm256_cmpeq_epi64(v, keys);
-                    let mask = _mm256_movemask_epi8(v);
-                    if mask != 0 {
-                        return Some(left + mask.trailing_zeros() as usize / 8);
-                    }
-                }
-            }
+    // elements ahead of that one are all before `target`, so the first equal lane is the answer
+    let from = base.min(len - window);
+    unsafe {
+        let keys = splat(target);
+        let mut mask = 0u64;
+        for i in 0..WINDOW_VECTORS {
+            let v = load(nums[from + i * unit_size..].as_ptr());
+            mask |= (lane_mask::<T>(cmpeq::<T>(v, keys)) as u64) << (i * unit_size);
         }
+        (mask != 0).then(|| from + mask.trailing_zeros() as usize)
     }
-    None
 }
 
 #[cfg(test)]
+#[allow(clippy::useless_conversion, clippy::identity_op)]
 mod tests {
     use crate::simd::simd_bst::binary_search;
+    use crate::simd::Order;
 
     #[test]
     fn test_u8s_sse_bst() {
         for size in 1..=u8::MAX {
             let nums = (0..size).into_iter().collect::<Vec<_>>();
             for target in 0..size {
-                let res = binary_search(&nums, target);
+                let res = binary_search(&nums, target, Order::Ascending);
                 assert!(res.is_some());
                 assert_eq!(res.unwrap(), target as usize);
             }
@@ -355,7 +69,7 @@ mod tests {
             let i8s = (0 - half..0 + half).into_iter().collect::<Vec<_>>();
             let indexs = i8s.iter().enumerate().collect::<Vec<_>>();
             for (idx, target) in indexs {
-                let ans = binary_search(&i8s, *target);
+                let ans = binary_search(&i8s, *target, Order::Ascending);
                 assert!(ans.is_some());
                 assert_eq!(ans.unwrap(), idx);
             }
@@ -367,7 +81,7 @@ mod tests {
         for size in 1u16..=1024 {
             let nums = (0..size).into_iter().collect::<Vec<_>>();
             for target in 0..size {
-                let res = binary_search(&nums, target);
+                let res = binary_search(&nums, target, Order::Ascending);
                 assert!(res.is_some());
                 assert_eq!(res.unwrap(), target as usize);
             }
@@ -381,7 +95,7 @@ mod tests {
             let i16s = (0 - half..0 + half).into_iter().collect::<Vec<_>>();
             let indexs = i16s.iter().enumerate().collect::<Vec<_>>();
             for (idx, target) in indexs {
-                let ans = binary_search(&i16s, *target);
+                let ans = binary_search(&i16s, *target, Order::Ascending);
                 assert!(ans.is_some());
                 assert_eq!(ans.unwrap(), idx);
             }
@@ -393,7 +107,7 @@ mod tests {
         for size in 1u32..=1024 {
             let nums = (0..size).into_iter().collect::<Vec<_>>();
             for target in 0..size {
-                let res = binary_search(&nums, target);
+                let res = binary_search(&nums, target, Order::Ascending);
                 assert!(res.is_some());
                 assert_eq!(res.unwrap(), target as usize);
             }
@@ -407,7 +121,7 @@ mod tests {
             let i16s = (0 - half..0 + half).into_iter().collect::<Vec<_>>();
             let indexs = i16s.iter().enumerate().collect::<Vec<_>>();
             for (idx, target) in indexs {
-                let ans = binary_search(&i16s, *target);
+                let ans = binary_search(&i16s, *target, Order::Ascending);
                 assert!(ans.is_some());
                 assert_eq!(ans.unwrap(), idx);
             }
@@ -419,7 +133,7 @@ mod tests {
         for size in 1u64..=1024 {
             let nums = (0..size).into_iter().collect::<Vec<_>>();
             for target in 0..size {
-                let res = binary_search(&nums, target);
+                let res = binary_search(&nums, target, Order::Ascending);
                 assert!(res.is_some());
                 assert_eq!(res.unwrap(), target as usize);
             }
@@ -433,10 +147,56 @@ mod tests {
             let i64s = (0 - half..0 + half).into_iter().collect::<Vec<_>>();
             let indexs = i64s.iter().enumerate().collect::<Vec<_>>();
             for (idx, target) in indexs {
-                let ans = binary_search(&i64s, *target);
+                let ans = binary_search(&i64s, *target, Order::Ascending);
                 assert!(ans.is_some());
                 assert_eq!(ans.unwrap(), idx);
             }
         }
     }
+
+    #[test]
+    fn test_descending_bst() {
+        for size in 1..=u8::MAX {
+            let nums = (0..size).rev().collect::<Vec<_>>();
+            for target in 0..size {
+                let res = binary_search(&nums, target, Order::Descending);
+                assert_eq!(res, Some((size - 1 - target) as usize));
+            }
+        }
+        for size in 1i32..=1024 {
+            let nums = (-size / 2..size - size / 2).rev().collect::<Vec<_>>();
+            for (idx, target) in nums.iter().enumerate() {
+                let res = binary_search(&nums, *target, Order::Descending);
+                assert_eq!(res, Some(idx));
+            }
+        }
+        for size in 1u64..=1024 {
+            let nums = (0..size).rev().map(|i| i << 40).collect::<Vec<_>>();
+            for (idx, target) in nums.iter().enumerate() {
+                let res = binary_search(&nums, *target, Order::Descending);
+                assert_eq!(res, Some(idx));
+            }
+        }
+    }
+
+    #[test]
+    fn test_bst_edges() {
+        assert_eq!(binary_search::<u32>(&[], 1, Order::Ascending), None);
+        for size in [1usize, 2, 31, 32, 33, 64, 65, 1000, 4099] {
+            let nums = (0..size as u64).map(|i| i / 3 * 2 + 10).collect::<Vec<_>>();
+            let last = nums[size - 1];
+            for target in (0..10).chain(9..last + 3).chain([u64::MAX]) {
+                let first = nums.iter().position(|n| *n == target);
+                assert_eq!(binary_search(&nums, target, Order::Ascending), first);
+                let rev = nums.iter().rev().copied().collect::<Vec<_>>();
+                let first = rev.iter().position(|n| *n == target);
+                assert_eq!(binary_search(&rev, target, Order::Descending), first);
+            }
+        }
+        let i8s = [i8::MIN, i8::MIN, -1, 0, 0, 0, i8::MAX];
+        for target in i8::MIN..=i8::MAX {
+            let first = i8s.iter().position(|n| *n == target);
+            assert_eq!(binary_search(&i8s, target, Order::Ascending), first);
+        }
+    }
 }
@@ -0,0 +1,291 @@
+use std::fmt;
+use std::marker::PhantomData;
+use std::mem::size_of;
+
+use crate::simd::linear::{cmpeq, cmpgt, lane_mask, lanes, load_aligned, splat, to_signed, Vector};
+use crate::simd::SIMDField;
+
+/// bytes of keys a `SimdSmallSet` can hold, two AVX2 or four SSE vectors
+pub const SMALL_SET_BYTES: usize = 64;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SmallSetError {
+    /// the set already holds `capacity` keys
+    CapacityExceeded { capacity: usize },
+}
+
+impl fmt::Display for SmallSetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SmallSetError::CapacityExceeded { capacity } => {
+                write!(f, "SimdSmallSet: more than {} keys", capacity)
+            }
+        }
+    }
+}
+
+impl std::error::Error for SmallSetError {}
+
+/// storage of `SimdSmallSet`, aligned for any vector width
+#[derive(Clone, Copy)]
+#[repr(C, align(32))]
+struct Slots([u8; SMALL_SET_BYTES]);
+
+/// up to `N` distinct keys kept sorted inline, in at most `SMALL_SET_BYTES`
+///
+/// the vectors covering `N` keys are compared against a broadcast of the probe all at once, so
+/// `contains` and `rank` are a fixed handful of compares and movemasks with no branches
+#[derive(Clone, Copy)]
+pub struct SimdSmallSet<T, const N: usize> {
+    len: usize,
+    slots: Slots,
+    _marker: PhantomData<T>,
+}
+
+impl<T: SIMDField, const N: usize> SimdSmallSet<T, N> {
+    // checked when a set is built, `N` keys must fit in the slots
+    const FITS: () = assert!(N > 0 && N * size_of::<T>() <= SMALL_SET_BYTES);
+    // vectors loaded per probe, the slots past them are never read
+    const VECTORS: usize = (N * size_of::<T>()).div_ceil(size_of::<Vector>());
+
+    pub fn new() -> Self {
+        let () = Self::FITS;
+        SimdSmallSet {
+            len: 0,
+            slots: Slots([0; SMALL_SET_BYTES]),
+            _marker: PhantomData,
+        }
+    }
+
+    /// collects `keys` in any order, duplicates are dropped
+    pub fn try_from_iter<I: IntoIterator<Item = T>>(keys: I) -> Result<Self, SmallSetError> {
+        let mut set = Self::new();
+        for key in keys {
+            set.insert(key)?;
+        }
+        Ok(set)
+    }
+
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.len == N
+    }
+
+    /// the keys in ascending order
+    pub fn as_slice(&self) -> &[T] {
+        &self.keys()[..self.len]
+    }
+
+    pub fn contains(&self, key: T) -> bool {
+        self.matches(|chunk, keys| unsafe { cmpeq::<T>(chunk, keys) }, key) != 0
+    }
+
+    /// number of keys less than `key`, which is its index if present
+    pub fn rank(&self, key: T) -> usize {
+        self.matches(
+            |chunk, keys| unsafe { cmpgt::<T>(to_signed::<T>(keys), to_signed::<T>(chunk)) },
+            key,
+        )
+        .count_ones() as usize
+    }
+
+    /// `Ok(false)` if `key` was already present, a full set only fails for a new key
+    pub fn insert(&mut self, key: T) -> Result<bool, SmallSetError> {
+        let at = self.rank(key);
+        if at < self.len && self.keys()[at] == key {
+            return Ok(false);
+        }
+        if self.is_full() {
+            return Err(SmallSetError::CapacityExceeded { capacity: N });
+        }
+        let len = self.len;
+        let keys = self.keys_mut();
+        keys[at..=len].rotate_right(1);
+        keys[at] = key;
+        self.len += 1;
+        Ok(true)
+    }
+
+    /// whether `key` was present
+    pub fn remove(&mut self, key: T) -> bool {
+        let at = self.rank(key);
+        if at >= self.len || self.keys()[at] != key {
+            return false;
+        }
+        let len = self.len;
+        self.keys_mut()[at..len].rotate_left(1);
+        self.len -= 1;
+        true
+    }
+
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+
+    // one bit per key for which `cmp(chunk, splat(key))` holds
+    #[inline(always)]
+    fn matches(&self, cmp: impl Fn(Vector, Vector) -> Vector, key: T) -> u64 {
+        let unit_size = lanes::<T>();
+        let keys = unsafe { splat(key) };
+        let mut mask = 0u64;
+        for v in 0..Self::VECTORS {
+            let hits = unsafe {
+                lane_mask::<T>(cmp(
+                    load_aligned(self.keys()[v * unit_size..].as_ptr()),
+                    keys,
+                ))
+            };
+            mask |= (hits as u64) << (v * unit_size);
+        }
+        // slots past `len` hold stale keys
+        mask & u64::MAX.checked_shr(64 - self.len as u32).unwrap_or(0)
+    }
+
+    // every slot, the first `len` are the keys
+    fn keys(&self) -> &[T] {
+        let len = SMALL_SET_BYTES / size_of::<T>();
+        // `Slots` is plain bytes aligned for any `SIMDField`, which are all plain integers
+        unsafe { std::slice::from_raw_parts(self.slots.0.as_ptr() as *const T, len) }
+    }
+
+    fn keys_mut(&mut self) -> &mut [T] {
+        let len = SMALL_SET_BYTES / size_of::<T>();
+        unsafe { std::slice::from_raw_parts_mut(self.slots.0.as_mut_ptr() as *mut T, len) }
+    }
+}
+
+impl<T: SIMDField, const N: usize> Default for SimdSmallSet<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: SIMDField, const N: usize> PartialEq for SimdSmallSet<T, N> {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_slice() == other.as_slice()
+    }
+}
+
+impl<T: SIMDField, const N: usize> Eq for SimdSmallSet<T, N> {}
+
+impl<T: SIMDField + fmt::Debug, const N: usize> fmt::Debug for SimdSmallSet<T, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.as_slice()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::simd::SIMDField;
+    use crate::small_set::{SimdSmallSet, SmallSetError};
+
+    fn check_small_set<T: SIMDField + std::fmt::Debug, const N: usize>(keys: &[T], probes: &[T]) {
+        let set = SimdSmallSet::<T, N>::try_from_iter(keys.iter().rev().copied()).unwrap();
+        let mut sorted = keys.to_vec();
+        sorted.sort_unstable();
+        sorted.dedup();
+        assert_eq!(set.as_slice(), sorted);
+        for p in probes.iter().chain(keys) {
+            assert_eq!(set.contains(*p), sorted.contains(p), "{:?}", p);
+            assert_eq!(set.rank(*p), sorted.partition_point(|k| k < p), "{:?}", p);
+        }
+    }
+
+    #[test]
+    fn test_u8s_small_set() {
+        let probes = (0..=u8::MAX).collect::<Vec<_>>();
+        check_small_set::<u8, 4>(&[9, 0, u8::MAX, 9, 200], &probes);
+        check_small_set::<u8, 16>(&(0..16).map(|i| i * 13).collect::<Vec<_>>(), &probes);
+        check_small_set::<u8, 64>(&(0..64).map(|i| i * 4 + 1).collect::<Vec<_>>(), &probes);
+        check_small_set::<u8, 64>(&[], &probes);
+    }
+
+    #[test]
+    fn test_i16s_small_set() {
+        let probes = (-400..400i16)
+            .chain([i16::MIN, i16::MAX])
+            .collect::<Vec<_>>();
+        check_small_set::<i16, 8>(&[-300, 7, i16::MIN, i16::MAX], &probes);
+        check_small_set::<i16, 20>(&(0..20).map(|i| i * 37 - 350).collect::<Vec<_>>(), &probes);
+        check_small_set::<i16, 32>(&(0..32).map(|i| i * 3 - 50).collect::<Vec<_>>(), &probes);
+    }
+
+    #[test]
+    fn test_u32s_small_set() {
+        let probes = (0..100u32)
+            .map(|p| p << 25)
+            .chain([1, u32::MAX])
+            .collect::<Vec<_>>();
+        check_small_set::<u32, 5>(&[u32::MAX, 0, 3 << 25], &probes);
+        check_small_set::<u32, 16>(&(0..16).map(|i| (i * 5) << 25).collect::<Vec<_>>(), &probes);
+    }
+
+    #[test]
+    fn test_i64s_small_set() {
+        let probes = (-40..40i64)
+            .map(|p| p << 56)
+            .chain([i64::MIN, i64::MAX, 1])
+            .collect::<Vec<_>>();
+        check_small_set::<i64, 3>(&[i64::MIN, 0, i64::MAX], &probes);
+        check_small_set::<i64, 8>(
+            &(0..8).map(|i| (i * 9 - 30) << 56).collect::<Vec<_>>(),
+            &probes,
+        );
+    }
+
+    #[test]
+    fn test_u64s_small_set() {
+        let probes = [0, 1, 2, 1 << 63, u64::MAX - 1, u64::MAX];
+        check_small_set::<u64, 4>(&[u64::MAX, 1, 1 << 63], &probes);
+    }
+
+    #[test]
+    fn test_small_set_insert_remove() {
+        let mut set = SimdSmallSet::<u16, 4>::new();
+        assert!(set.is_empty());
+        assert_eq!(set.insert(30), Ok(true));
+        assert_eq!(set.insert(10), Ok(true));
+        assert_eq!(set.insert(30), Ok(false));
+        assert_eq!(set.insert(20), Ok(true));
+        assert_eq!(set.insert(40), Ok(true));
+        assert!(set.is_full());
+        assert_eq!(set.insert(20), Ok(false));
+        assert_eq!(
+            set.insert(25),
+            Err(SmallSetError::CapacityExceeded { capacity: 4 })
+        );
+        assert_eq!(set.as_slice(), [10, 20, 30, 40]);
+        assert!(set.remove(20));
+        assert!(!set.remove(20));
+        assert!(!set.contains(20));
+        assert_eq!(set.rank(35), 2);
+        assert_eq!(set.insert(25), Ok(true));
+        assert_eq!(format!("{:?}", set), "{10, 25, 30, 40}");
+        for k in [10, 25, 30, 40] {
+            assert!(set.remove(k));
+        }
+        assert!(set.is_empty());
+        // stale slots are never reported
+        assert!(!set.contains(40));
+        assert_eq!(set.rank(u16::MAX), 0);
+        assert_eq!(
+            SimdSmallSet::<i8, 2>::try_from_iter([1, 2, 3]),
+            Err(SmallSetError::CapacityExceeded { capacity: 2 })
+        );
+        assert_eq!(
+            SimdSmallSet::<i8, 2>::try_from_iter([2, 1, 2]).unwrap(),
+            SimdSmallSet::<i8, 2>::try_from_iter([1, 2]).unwrap()
+        );
+    }
+}
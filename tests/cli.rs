@@ -0,0 +1,172 @@
+//! runs the `bst` binary on temporary files
+
+use std::fs;
+use std::path::Path;
+
+use assert_cmd::Command;
+use tempfile::TempDir;
+
+fn bst() -> Command {
+    Command::cargo_bin("bst").unwrap()
+}
+
+fn stdout(cmd: &mut Command) -> String {
+    String::from_utf8(cmd.assert().success().get_output().stdout.clone()).unwrap()
+}
+
+fn stderr(cmd: &mut Command) -> String {
+    String::from_utf8(cmd.assert().failure().get_output().stderr.clone()).unwrap()
+}
+
+// `values` one per line built into `<dir>/<name>.bin` as `elem`
+fn build(dir: &TempDir, name: &str, values: &str, elem: &str) -> String {
+    let input = dir.path().join(format!("{}.txt", name));
+    let output = dir.path().join(format!("{}.bin", name));
+    fs::write(&input, values).unwrap();
+    bst()
+        .args(["build", "--type", elem])
+        .arg(&input)
+        .arg(&output)
+        .assert()
+        .success();
+    output.to_str().unwrap().to_string()
+}
+
+#[test]
+fn test_cli_queries() {
+    let dir = tempfile::tempdir().unwrap();
+    let file = build(&dir, "nums", "9\n3\n\n7\n3\n-1\n", "i32");
+    assert_eq!(
+        stdout(bst().args(["verify", &file])),
+        "ok: 5 elements of i32\n"
+    );
+    assert_eq!(
+        stdout(bst().args(["lookup", &file, "--", "3", "7", "8", "-1"])),
+        "3\t1\n7\t3\n8\t-\n-1\t0\n"
+    );
+    assert_eq!(
+        stdout(bst().args(["lookup", &file]).write_stdin("9 2\n3\n")),
+        "9\t4\n2\t-\n3\t1\n"
+    );
+    assert_eq!(stdout(bst().args(["range", &file, "0", "8"])), "1\t4\n");
+    assert_eq!(stdout(bst().args(["range", &file, "8", "0"])), "4\t4\n");
+    assert_eq!(
+        stdout(bst().args(["count", &file, "3", "9", "4"])),
+        "3\t2\n9\t1\n4\t0\n"
+    );
+    assert!(stderr(bst().args(["lookup", &file, "x"])).contains("invalid number `x`"));
+}
+
+#[test]
+fn test_cli_descending_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("desc.bin");
+    let nums = [9i32, 7, 3, 3, -1];
+    let mut out = fs::File::create(&path).unwrap();
+    bst_rs::write_sorted_array_ordered(&mut out, &nums, bst_rs::Order::Descending).unwrap();
+    drop(out);
+    let file = path.to_str().unwrap();
+    assert_eq!(
+        stdout(bst().args(["verify", file])),
+        "ok: 5 elements of i32, descending\n"
+    );
+    assert_eq!(
+        stdout(bst().args(["lookup", file, "--", "3", "7", "8", "-1"])),
+        "3\t2\n7\t1\n8\t-\n-1\t4\n"
+    );
+    // `[0, 8)` holds 7, 3, 3
+    assert_eq!(stdout(bst().args(["range", file, "0", "8"])), "1\t4\n");
+    assert_eq!(stdout(bst().args(["range", file, "8", "0"])), "4\t4\n");
+    assert_eq!(
+        stdout(bst().args(["count", file, "3", "9", "4"])),
+        "3\t2\n9\t1\n4\t0\n"
+    );
+    let bench = stdout(bst().args(["bench", file, "--queries", "100"]));
+    for line in bench.lines() {
+        assert!(!line.ends_with("(0 hits)"), "{}", bench);
+    }
+}
+
+#[test]
+fn test_cli_build_csv() {
+    let dir = tempfile::tempdir().unwrap();
+    let input = dir.path().join("table.csv");
+    let output = dir.path().join("table.bin");
+    fs::write(&input, "id;value\n1;500\n2;20\n3;65535\n").unwrap();
+    bst()
+        .args([
+            "build",
+            "--type",
+            "u16",
+            "--column",
+            "1",
+            "--delimiter",
+            ";",
+            "--header",
+        ])
+        .arg(&input)
+        .arg(&output)
+        .assert()
+        .success();
+    let file = output.to_str().unwrap();
+    assert_eq!(
+        stdout(bst().args(["verify", file])),
+        "ok: 3 elements of u16\n"
+    );
+    assert_eq!(
+        stdout(bst().args(["lookup", file, "20", "65535"])),
+        "20\t0\n65535\t2\n"
+    );
+    // out of range for the element type
+    fs::write(&input, "id;value\n1;70000\n").unwrap();
+    let err = stderr(
+        bst()
+            .args([
+                "build",
+                "--type",
+                "u16",
+                "--column",
+                "1",
+                "--delimiter",
+                ";",
+                "--header",
+            ])
+            .arg(&input)
+            .arg(&output),
+    );
+    assert!(err.contains("line 2"), "{}", err);
+}
+
+// overwrite the bytes at `at` of the file
+fn patch(file: &str, at: usize, bytes: &[u8]) {
+    let mut data = fs::read(file).unwrap();
+    data[at..at + bytes.len()].copy_from_slice(bytes);
+    fs::write(file, data).unwrap();
+}
+
+#[test]
+fn test_cli_type_tag() {
+    let dir = tempfile::tempdir().unwrap();
+    let file = build(&dir, "nums", "1\n2\n3\n", "u32");
+    // no element type has 3 bytes
+    patch(&file, 12, &[0x03]);
+    assert!(stderr(bst().args(["verify", &file])).contains("unknown type tag 0x03"));
+    // a valid tag of another width doesn't match the payload size
+    patch(&file, 12, &[0x08]);
+    assert!(stderr(bst().args(["lookup", &file, "2"])).contains("truncated payload"));
+}
+
+#[test]
+fn test_cli_corrupt_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let file = build(&dir, "nums", "1\n2\n3\n", "u64");
+    let len = fs::metadata(&file).unwrap().len() as usize;
+    patch(&file, len - 1, &[0xFF]);
+    assert!(stderr(bst().args(["verify", &file])).contains("checksum mismatch"));
+    assert!(stderr(bst().args(["count", &file, "1"])).contains("checksum mismatch"));
+    patch(&file, 0, b"NOTSORT\0");
+    assert!(stderr(bst().args(["verify", &file])).contains("bad magic"));
+    let missing = Path::new(&file).with_extension("missing");
+    let err = stderr(bst().arg("verify").arg(&missing));
+    assert!(err.starts_with("bst: "), "{}", err);
+}